@@ -0,0 +1,4 @@
+-- Remove quoted post reference and Reposts table
+DROP INDEX IF EXISTS posts_quoted_post_id_idx;
+ALTER TABLE posts DROP COLUMN quoted_post_id;
+DROP TABLE IF EXISTS reposts;
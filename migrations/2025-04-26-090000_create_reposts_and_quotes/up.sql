@@ -0,0 +1,12 @@
+-- Create Reposts table
+CREATE TABLE reposts (
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, post_id)
+);
+
+-- Add quoted post reference to posts
+-- No foreign key on purpose: a quote post outlives the post it quotes
+ALTER TABLE posts ADD COLUMN quoted_post_id INTEGER;
+CREATE INDEX posts_quoted_post_id_idx ON posts (quoted_post_id);
@@ -0,0 +1,2 @@
+-- Drop Likes table
+DROP TABLE IF EXISTS likes;
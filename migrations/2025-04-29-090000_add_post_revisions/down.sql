@@ -0,0 +1,4 @@
+-- Drop Post revisions table and edit tracking columns
+DROP TABLE IF EXISTS post_revisions;
+ALTER TABLE posts DROP COLUMN edit_count;
+ALTER TABLE posts DROP COLUMN edited_at;
@@ -0,0 +1,13 @@
+-- Track edits on posts
+ALTER TABLE posts ADD COLUMN edited_at TIMESTAMP;
+ALTER TABLE posts ADD COLUMN edit_count INTEGER NOT NULL DEFAULT 0;
+
+-- Create Post revisions table holding the superseded versions of each post
+CREATE TABLE post_revisions (
+    id SERIAL PRIMARY KEY,
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    content VARCHAR NOT NULL,
+    created_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX post_revisions_post_id_idx ON post_revisions (post_id);
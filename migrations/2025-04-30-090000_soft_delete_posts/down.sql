@@ -0,0 +1,5 @@
+-- Remove moderator flag and soft delete columns
+ALTER TABLE users DROP COLUMN is_moderator;
+DROP INDEX IF EXISTS posts_deleted_at_idx;
+ALTER TABLE posts DROP COLUMN deleted_by;
+ALTER TABLE posts DROP COLUMN deleted_at;
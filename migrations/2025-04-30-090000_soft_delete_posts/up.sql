@@ -0,0 +1,7 @@
+-- Mark posts as deleted instead of removing them right away
+ALTER TABLE posts ADD COLUMN deleted_at TIMESTAMP;
+ALTER TABLE posts ADD COLUMN deleted_by INTEGER REFERENCES users(id);
+CREATE INDEX posts_deleted_at_idx ON posts (deleted_at);
+
+-- Add moderator flag to users table
+ALTER TABLE users ADD COLUMN is_moderator BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,3 @@
+-- Drop tables in reverse order to respect foreign key constraints
+DROP TABLE IF EXISTS post_hashtags;
+DROP TABLE IF EXISTS hashtags;
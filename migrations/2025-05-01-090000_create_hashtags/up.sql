@@ -0,0 +1,15 @@
+-- Create Hashtags table, names are stored normalized to lowercase
+CREATE TABLE hashtags (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Create Post hashtags table linking posts to the hashtags they contain
+CREATE TABLE post_hashtags (
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    hashtag_id INTEGER NOT NULL REFERENCES hashtags(id) ON DELETE CASCADE,
+    PRIMARY KEY (post_id, hashtag_id)
+);
+
+CREATE INDEX post_hashtags_hashtag_id_idx ON post_hashtags (hashtag_id, post_id);
@@ -0,0 +1,4 @@
+-- Drop tables in reverse order to respect foreign key constraints
+DROP INDEX IF EXISTS posts_created_at_idx;
+DROP TABLE IF EXISTS trend_entries;
+DROP TABLE IF EXISTS trend_snapshots;
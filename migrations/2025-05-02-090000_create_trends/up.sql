@@ -0,0 +1,22 @@
+-- Create Trend snapshots table, one row per computation and window
+CREATE TABLE trend_snapshots (
+    id SERIAL PRIMARY KEY,
+    window_minutes INTEGER NOT NULL,
+    computed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX trend_snapshots_window_idx ON trend_snapshots (window_minutes, computed_at);
+
+-- Create Trend entries table holding the ranked hashtags of each snapshot
+CREATE TABLE trend_entries (
+    snapshot_id INTEGER NOT NULL REFERENCES trend_snapshots(id) ON DELETE CASCADE,
+    rank INTEGER NOT NULL,
+    hashtag_id INTEGER NOT NULL REFERENCES hashtags(id) ON DELETE CASCADE,
+    score DOUBLE PRECISION NOT NULL,
+    post_count INTEGER NOT NULL,
+    author_count INTEGER NOT NULL,
+    PRIMARY KEY (snapshot_id, rank)
+);
+
+-- Speed up scanning recent posts
+CREATE INDEX posts_created_at_idx ON posts (created_at);
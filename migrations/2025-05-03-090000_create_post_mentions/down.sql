@@ -0,0 +1,3 @@
+-- Drop Post mentions table
+DROP INDEX IF EXISTS users_username_lower_idx;
+DROP TABLE IF EXISTS post_mentions;
@@ -0,0 +1,11 @@
+-- Create Post mentions table linking posts to the users they mention
+CREATE TABLE post_mentions (
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    PRIMARY KEY (post_id, user_id)
+);
+
+CREATE INDEX post_mentions_user_id_idx ON post_mentions (user_id, post_id);
+
+-- Usernames are matched case-insensitively
+CREATE INDEX users_username_lower_idx ON users (lower(username));
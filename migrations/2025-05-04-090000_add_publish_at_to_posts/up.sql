@@ -0,0 +1,4 @@
+-- Scheduled posts keep the time they are due in publish_at until they are published
+ALTER TABLE posts ADD COLUMN publish_at TIMESTAMP;
+
+CREATE INDEX posts_publish_at_idx ON posts (publish_at) WHERE publish_at IS NOT NULL;
@@ -0,0 +1,2 @@
+-- Drop Drafts table
+DROP TABLE IF EXISTS drafts;
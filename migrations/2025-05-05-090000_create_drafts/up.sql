@@ -0,0 +1,13 @@
+-- Create Drafts table
+-- No foreign key on quoted_post_id: the quoted post is checked when publishing
+CREATE TABLE drafts (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    content VARCHAR NOT NULL DEFAULT '',
+    quoted_post_id INTEGER,
+    version INTEGER NOT NULL DEFAULT 1,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX drafts_user_id_idx ON drafts (user_id, updated_at);
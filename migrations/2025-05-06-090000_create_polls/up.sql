@@ -0,0 +1,26 @@
+-- Create Polls table
+-- A poll closes duration_minutes after its post is published
+CREATE TABLE polls (
+    post_id INTEGER PRIMARY KEY REFERENCES posts(id) ON DELETE CASCADE,
+    duration_minutes INTEGER NOT NULL
+);
+
+-- Create Poll options table
+CREATE TABLE poll_options (
+    id SERIAL PRIMARY KEY,
+    post_id INTEGER NOT NULL REFERENCES polls(post_id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    label VARCHAR NOT NULL,
+    UNIQUE (post_id, position)
+);
+
+-- Create Poll votes table, one vote per user and poll
+CREATE TABLE poll_votes (
+    post_id INTEGER NOT NULL REFERENCES polls(post_id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    option_id INTEGER NOT NULL REFERENCES poll_options(id) ON DELETE CASCADE,
+    voted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (post_id, user_id)
+);
+
+CREATE INDEX poll_votes_option_id_idx ON poll_votes (option_id);
@@ -0,0 +1,3 @@
+-- Drop Media tables
+DROP TABLE IF EXISTS media_variants;
+DROP TABLE IF EXISTS media;
@@ -0,0 +1,26 @@
+-- Create Media table for uploaded images
+-- Media is uploaded first and attached to a post when the post is created
+CREATE TABLE media (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    post_id INTEGER REFERENCES posts(id) ON DELETE CASCADE,
+    position INTEGER,
+    content_type VARCHAR NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    storage_key VARCHAR NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX media_post_id_idx ON media (post_id, position);
+
+-- Create Media variants table for the resized copies of each image
+CREATE TABLE media_variants (
+    media_id INTEGER NOT NULL REFERENCES media(id) ON DELETE CASCADE,
+    name VARCHAR NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    storage_key VARCHAR NOT NULL,
+    PRIMARY KEY (media_id, name)
+);
@@ -0,0 +1,3 @@
+-- Remove image placeholders
+ALTER TABLE media DROP COLUMN IF EXISTS dominant_color;
+ALTER TABLE media DROP COLUMN IF EXISTS blurhash;
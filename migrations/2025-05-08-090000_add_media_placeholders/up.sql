@@ -0,0 +1,4 @@
+-- Placeholders shown while an image loads. Images uploaded before they were
+-- computed have none.
+ALTER TABLE media ADD COLUMN blurhash VARCHAR;
+ALTER TABLE media ADD COLUMN dominant_color VARCHAR(7);
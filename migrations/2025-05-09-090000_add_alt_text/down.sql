@@ -0,0 +1,3 @@
+-- Drop User settings table and alt text
+DROP TABLE IF EXISTS user_settings;
+ALTER TABLE media DROP COLUMN IF EXISTS alt_text;
@@ -0,0 +1,9 @@
+-- Alt text describing each image, for people using screen readers
+ALTER TABLE media ADD COLUMN alt_text VARCHAR;
+
+-- Create User settings table. Users who never changed a setting have no row and get
+-- the defaults.
+CREATE TABLE user_settings (
+    user_id INTEGER PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    require_alt_text BOOLEAN NOT NULL DEFAULT FALSE
+);
@@ -0,0 +1,3 @@
+-- Drop Link card tables
+DROP TABLE IF EXISTS post_cards;
+DROP TABLE IF EXISTS link_cards;
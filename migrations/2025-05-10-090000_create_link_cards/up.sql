@@ -0,0 +1,22 @@
+-- Create Link cards table, caching the preview of each URL found in posts
+-- Cards are fetched in the background; fetched_at stays NULL until they are. Pages
+-- without a preview keep a NULL title.
+CREATE TABLE link_cards (
+    id SERIAL PRIMARY KEY,
+    url VARCHAR NOT NULL UNIQUE,
+    card_type VARCHAR,
+    title VARCHAR,
+    description VARCHAR,
+    image_url VARCHAR,
+    site_name VARCHAR,
+    fetched_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX link_cards_unfetched_idx ON link_cards (id) WHERE fetched_at IS NULL;
+
+-- Create Post cards table, linking each post to the card of the first URL in it
+CREATE TABLE post_cards (
+    post_id INTEGER PRIMARY KEY REFERENCES posts(id) ON DELETE CASCADE,
+    link_card_id INTEGER NOT NULL REFERENCES link_cards(id) ON DELETE CASCADE
+);
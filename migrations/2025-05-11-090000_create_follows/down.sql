@@ -0,0 +1,2 @@
+-- Drop Follows table
+DROP TABLE IF EXISTS follows;
@@ -0,0 +1,3 @@
+-- Remove post visibility
+ALTER TABLE user_settings DROP COLUMN IF EXISTS default_visibility;
+ALTER TABLE posts DROP COLUMN IF EXISTS visibility;
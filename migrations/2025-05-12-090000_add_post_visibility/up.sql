@@ -0,0 +1,7 @@
+-- Who can see each post: public, unlisted, followers or mentioned
+ALTER TABLE posts ADD COLUMN visibility VARCHAR NOT NULL DEFAULT 'public'
+    CHECK (visibility IN ('public', 'unlisted', 'followers', 'mentioned'));
+
+-- Visibility of new posts when none is given
+ALTER TABLE user_settings ADD COLUMN default_visibility VARCHAR NOT NULL DEFAULT 'public'
+    CHECK (default_visibility IN ('public', 'unlisted', 'followers', 'mentioned'));
@@ -0,0 +1,2 @@
+-- Drop Pinned Posts table
+DROP TABLE IF EXISTS pinned_posts;
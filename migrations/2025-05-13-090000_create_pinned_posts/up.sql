@@ -0,0 +1,9 @@
+-- Create Pinned Posts table
+CREATE TABLE pinned_posts (
+    post_id INTEGER PRIMARY KEY REFERENCES posts(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    position INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX pinned_posts_user_id_idx ON pinned_posts (user_id, position);
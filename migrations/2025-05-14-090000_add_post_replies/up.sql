@@ -0,0 +1,10 @@
+-- Add replies to posts
+-- No foreign keys on purpose: like quotes, replies outlive the posts they answer
+ALTER TABLE posts ADD COLUMN in_reply_to_id INTEGER;
+CREATE INDEX posts_in_reply_to_id_idx ON posts (in_reply_to_id);
+
+-- Posts share the conversation ID of the post their conversation started with,
+-- which is their own ID if they started it
+ALTER TABLE posts ADD COLUMN conversation_id INTEGER;
+UPDATE posts SET conversation_id = id;
+CREATE INDEX posts_conversation_id_idx ON posts (conversation_id, created_at);
@@ -0,0 +1,2 @@
+-- Remove reply audience
+ALTER TABLE posts DROP COLUMN IF EXISTS reply_audience;
@@ -0,0 +1,3 @@
+-- Who can reply to each post: everyone, following or mentioned
+ALTER TABLE posts ADD COLUMN reply_audience VARCHAR NOT NULL DEFAULT 'everyone'
+    CHECK (reply_audience IN ('everyone', 'following', 'mentioned'));
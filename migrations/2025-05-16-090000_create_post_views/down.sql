@@ -0,0 +1,3 @@
+-- Drop Post View tables
+DROP TABLE IF EXISTS post_view_counts;
+DROP TABLE IF EXISTS post_view_receipts;
@@ -0,0 +1,21 @@
+-- Create Post View Receipts table
+-- Views already counted, so that each viewer counts once per post, kind of view and
+-- window. Only the current window is kept.
+CREATE TABLE post_view_receipts (
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    viewer_id INTEGER NOT NULL REFERENCES users(id),
+    kind VARCHAR NOT NULL CHECK (kind IN ('impression', 'detail')),
+    window_start TIMESTAMP NOT NULL,
+    PRIMARY KEY (post_id, viewer_id, kind, window_start)
+);
+
+CREATE INDEX post_view_receipts_window_start_idx ON post_view_receipts (window_start);
+
+-- Create Post View Counts table
+CREATE TABLE post_view_counts (
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    hour TIMESTAMP NOT NULL,
+    impressions INTEGER NOT NULL DEFAULT 0,
+    detail_views INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (post_id, hour)
+);
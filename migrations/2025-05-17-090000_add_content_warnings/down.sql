@@ -0,0 +1,5 @@
+-- Remove content warnings
+ALTER TABLE user_settings DROP COLUMN IF EXISTS expand_content_warnings;
+ALTER TABLE posts DROP COLUMN IF EXISTS sensitive_by_moderator;
+ALTER TABLE posts DROP COLUMN IF EXISTS sensitive;
+ALTER TABLE posts DROP COLUMN IF EXISTS content_warning;
@@ -0,0 +1,8 @@
+-- Content warning shown instead of the content until expanded, and whether the post
+-- is sensitive, as set by its author or forced by a moderator
+ALTER TABLE posts ADD COLUMN content_warning VARCHAR;
+ALTER TABLE posts ADD COLUMN sensitive BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE posts ADD COLUMN sensitive_by_moderator BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Whether posts with content warnings or sensitive media are shown expanded
+ALTER TABLE user_settings ADD COLUMN expand_content_warnings BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,5 @@
+-- Remove post languages
+ALTER TABLE user_settings DROP COLUMN IF EXISTS preferred_languages;
+DROP INDEX IF EXISTS posts_lang_idx;
+ALTER TABLE posts DROP COLUMN IF EXISTS lang_overridden;
+ALTER TABLE posts DROP COLUMN IF EXISTS lang;
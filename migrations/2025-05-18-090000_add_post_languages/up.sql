@@ -0,0 +1,8 @@
+-- Language of the post as an ISO 639-1 code, detected from its content unless the
+-- author chose it. Posts written before detection, or too short to tell, have none.
+ALTER TABLE posts ADD COLUMN lang VARCHAR;
+ALTER TABLE posts ADD COLUMN lang_overridden BOOLEAN NOT NULL DEFAULT FALSE;
+CREATE INDEX posts_lang_idx ON posts (lang);
+
+-- Languages the user wants to read in their feed, all of them when empty
+ALTER TABLE user_settings ADD COLUMN preferred_languages TEXT[] NOT NULL DEFAULT '{}';
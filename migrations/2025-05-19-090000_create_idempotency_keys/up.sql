@@ -0,0 +1,17 @@
+-- Responses to mutating requests sent with an Idempotency-Key header, replayed when a
+-- client retries the same request with the same key. Requests still being handled
+-- have no status code yet, and their claim is refreshed until they are.
+CREATE TABLE idempotency_keys (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    key VARCHAR(255) NOT NULL,
+    fingerprint VARCHAR(64) NOT NULL,
+    status_code INTEGER,
+    content_type VARCHAR,
+    response_body BYTEA,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    claimed_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, key)
+);
+
+-- Expired keys are purged by age
+CREATE INDEX idempotency_keys_created_at_idx ON idempotency_keys (created_at);
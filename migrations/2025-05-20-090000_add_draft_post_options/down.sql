@@ -0,0 +1,10 @@
+-- Remove post options from drafts
+ALTER TABLE drafts DROP COLUMN IF EXISTS poll_duration_minutes;
+ALTER TABLE drafts DROP COLUMN IF EXISTS poll_options;
+ALTER TABLE drafts DROP COLUMN IF EXISTS media_ids;
+ALTER TABLE drafts DROP COLUMN IF EXISTS lang;
+ALTER TABLE drafts DROP COLUMN IF EXISTS sensitive;
+ALTER TABLE drafts DROP COLUMN IF EXISTS content_warning;
+ALTER TABLE drafts DROP COLUMN IF EXISTS visibility;
+ALTER TABLE drafts DROP COLUMN IF EXISTS reply_audience;
+ALTER TABLE drafts DROP COLUMN IF EXISTS in_reply_to_id;
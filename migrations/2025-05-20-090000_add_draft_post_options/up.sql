@@ -0,0 +1,13 @@
+-- Everything a new post can have, so that publishing a draft makes the post it was
+-- going to be. Nothing is checked until the draft is published.
+ALTER TABLE drafts ADD COLUMN in_reply_to_id INTEGER;
+ALTER TABLE drafts ADD COLUMN reply_audience VARCHAR;
+ALTER TABLE drafts ADD COLUMN visibility VARCHAR;
+ALTER TABLE drafts ADD COLUMN content_warning VARCHAR;
+ALTER TABLE drafts ADD COLUMN sensitive BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE drafts ADD COLUMN lang VARCHAR;
+ALTER TABLE drafts ADD COLUMN media_ids INTEGER[] NOT NULL DEFAULT '{}';
+
+-- The poll, if any, set together
+ALTER TABLE drafts ADD COLUMN poll_options TEXT[];
+ALTER TABLE drafts ADD COLUMN poll_duration_minutes INTEGER;
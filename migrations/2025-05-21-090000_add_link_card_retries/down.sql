@@ -0,0 +1,7 @@
+-- Remove link card retries
+DROP INDEX IF EXISTS link_cards_next_fetch_at_idx;
+CREATE INDEX IF NOT EXISTS link_cards_unfetched_idx ON link_cards (id) WHERE fetched_at IS NULL;
+
+ALTER TABLE link_cards DROP COLUMN IF EXISTS next_fetch_at;
+ALTER TABLE link_cards DROP COLUMN IF EXISTS attempts;
+ALTER TABLE link_cards DROP COLUMN IF EXISTS fetch_error;
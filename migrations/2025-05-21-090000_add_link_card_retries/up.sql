@@ -0,0 +1,14 @@
+-- Why the last fetch of a card failed, how many times in a row it failed for a reason
+-- that may go away, and when it is fetched next: right away for new cards, after a
+-- backoff for failed ones and after a while for the others, to refresh them
+ALTER TABLE link_cards ADD COLUMN fetch_error VARCHAR;
+ALTER TABLE link_cards ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE link_cards ADD COLUMN next_fetch_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP;
+
+-- Cards fetched already are refreshed a week later, except the ones without a
+-- preview, which may have failed for a reason that went away
+UPDATE link_cards SET next_fetch_at = fetched_at + INTERVAL '7 days'
+WHERE fetched_at IS NOT NULL AND title IS NOT NULL;
+
+DROP INDEX IF EXISTS link_cards_unfetched_idx;
+CREATE INDEX link_cards_next_fetch_at_idx ON link_cards (next_fetch_at);
@@ -0,0 +1,143 @@
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, get, web};
+use diesel::dsl::{self, IntervalDsl, count_star, now};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::IntoParams;
+
+use crate::models::{
+    post::Post,
+    user::AuthedUserId,
+    view_count::{PostAnalytics, ViewCount},
+};
+use crate::schema::{likes, post_view_counts, posts, reposts};
+use crate::util::db::DbPool;
+
+const DEFAULT_HOURS: i64 = 7 * 24;
+const MAX_HOURS: i64 = 90 * 24;
+
+/// Query parameters for post analytics
+#[derive(Deserialize, IntoParams)]
+pub struct AnalyticsQuery {
+    /// Number of hours the time series covers, up to now (default 168, max 2160)
+    pub hours: Option<i64>,
+}
+
+/// Get the analytics of a post
+///
+/// Only the author can see how their post performs. Views are written in batches, so
+/// the latest ones show up after `VIEWS_FLUSH_INTERVAL_SECONDS`.
+#[utoipa::path(
+    params(AnalyticsQuery),
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Analytics of the post", body = PostAnalytics),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Post not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[get("/posts/{id}/analytics")]
+pub async fn get_post_analytics(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    query: web::Query<AnalyticsQuery>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let hours = query.hours.unwrap_or(DEFAULT_HOURS).clamp(1, MAX_HOURS);
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        // Other users' posts are not found
+        let post = posts::table
+            .find(post_id)
+            .filter(posts::user_id.eq(user_id))
+            .filter(posts::deleted_at.is_null())
+            .first::<Post>(&mut conn)
+            .optional()
+            .map_err(|_| "Database error finding post")?;
+
+        if post.is_none() {
+            return Ok::<_, &'static str>(None);
+        }
+
+        let (impressions, detail_views) = post_view_counts::table
+            .filter(post_view_counts::post_id.eq(post_id))
+            .select((
+                dsl::sum(post_view_counts::impressions),
+                dsl::sum(post_view_counts::detail_views),
+            ))
+            .first::<(Option<i64>, Option<i64>)>(&mut conn)
+            .map_err(|_| "Failed to load views")?;
+        let impressions = impressions.unwrap_or(0);
+
+        let series = post_view_counts::table
+            .filter(post_view_counts::post_id.eq(post_id))
+            .filter(post_view_counts::hour.gt(now - hours.hours()))
+            .order(post_view_counts::hour.asc())
+            .select((
+                post_view_counts::hour,
+                post_view_counts::impressions,
+                post_view_counts::detail_views,
+            ))
+            .load::<ViewCount>(&mut conn)
+            .map_err(|_| "Failed to load views")?;
+
+        let like_count = likes::table
+            .filter(likes::post_id.eq(post_id))
+            .select(count_star())
+            .first::<i64>(&mut conn)
+            .map_err(|_| "Failed to count engagements")?;
+        let repost_count = reposts::table
+            .filter(reposts::post_id.eq(post_id))
+            .select(count_star())
+            .first::<i64>(&mut conn)
+            .map_err(|_| "Failed to count engagements")?;
+        let quote_count = Post::live()
+            .filter(posts::quoted_post_id.eq(post_id))
+            .count()
+            .get_result::<i64>(&mut conn)
+            .map_err(|_| "Failed to count engagements")?;
+        let reply_count = Post::live()
+            .filter(posts::in_reply_to_id.eq(post_id))
+            .count()
+            .get_result::<i64>(&mut conn)
+            .map_err(|_| "Failed to count engagements")?;
+        let engagements = like_count + repost_count + quote_count + reply_count;
+
+        Ok(Some(PostAnalytics {
+            post_id,
+            impressions,
+            detail_views: detail_views.unwrap_or(0),
+            engagements,
+            engagement_rate: if impressions > 0 {
+                engagements as f64 / impressions as f64
+            } else {
+                0.0
+            },
+            series,
+        }))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Some(analytics))) => HttpResponse::Ok().json(analytics),
+        Ok(Ok(None)) => HttpResponse::NotFound().json(json!({
+            "error": "Post not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
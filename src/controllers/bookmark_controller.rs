@@ -0,0 +1,419 @@
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, delete, get, post, web};
+use diesel::pg::PgConnection;
+use diesel::upsert::excluded;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::models::{
+    bookmark::{BookmarkFolder, NewBookmark, NewBookmarkFolder},
+    post::Post,
+    post_view::PostView,
+    user::AuthedUserId,
+};
+use crate::schema::{bookmark_folders, bookmarks, posts};
+use crate::util::{
+    db::DbPool, language::LanguageFilter, pagination::Pagination, views::ViewRecorder,
+};
+
+const MAX_FOLDER_NAME_LENGTH: usize = 50;
+
+/// Used for API requests when bookmarking a post
+#[derive(Deserialize, ToSchema)]
+pub struct BookmarkRequest {
+    /// ID of the folder to file the bookmark in, if any
+    pub folder_id: Option<i32>,
+}
+
+/// Used for API requests when creating a bookmark folder
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({
+    "name": "Read later"
+}))]
+pub struct CreateBookmarkFolderRequest {
+    /// Name of the folder
+    pub name: String,
+}
+
+/// Query parameters for listing bookmarks
+#[derive(Deserialize, IntoParams)]
+pub struct BookmarkQuery {
+    /// Only list bookmarks filed in this folder
+    pub folder_id: Option<i32>,
+}
+
+// Helper function to check that a folder belongs to the user
+fn folder_belongs_to(
+    conn: &mut PgConnection,
+    folder_id: i32,
+    user_id: i32,
+) -> Result<bool, &'static str> {
+    let folder = bookmark_folders::table
+        .find(folder_id)
+        .filter(bookmark_folders::user_id.eq(user_id))
+        .first::<BookmarkFolder>(conn)
+        .optional()
+        .map_err(|_| "Database error finding folder")?;
+
+    Ok(folder.is_some())
+}
+
+/// Bookmark a post
+#[utoipa::path(
+    request_body(content = Option<BookmarkRequest>),
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 204, description = "Post bookmarked successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Post or folder not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[post("/posts/{id}/bookmark")]
+pub async fn bookmark_post(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    bookmark_req: Option<web::Json<BookmarkRequest>>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+    let folder_id = bookmark_req.and_then(|bookmark_req| bookmark_req.folder_id);
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        // Check if post exists
+        let post_exists = Post::visible_to(user_id)
+            .filter(posts::id.eq(post_id))
+            .first::<Post>(&mut conn)
+            .optional()
+            .map_err(|_| "Database error checking post")?;
+
+        if post_exists.is_none() {
+            return Ok::<_, &'static str>(Err("Post not found"));
+        }
+
+        if let Some(folder_id) = folder_id
+            && !folder_belongs_to(&mut conn, folder_id, user_id)?
+        {
+            return Ok(Err("Folder not found"));
+        }
+
+        // Bookmarking an already bookmarked post moves it to the given folder
+        diesel::insert_into(bookmarks::table)
+            .values(&NewBookmark {
+                user_id,
+                post_id,
+                folder_id,
+            })
+            .on_conflict((bookmarks::user_id, bookmarks::post_id))
+            .do_update()
+            .set(bookmarks::folder_id.eq(excluded(bookmarks::folder_id)))
+            .execute(&mut conn)
+            .map_err(|_| "Failed to bookmark post")?;
+
+        Ok(Ok(()))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Ok(()))) => HttpResponse::NoContent().finish(),
+        Ok(Ok(Err(e))) => HttpResponse::NotFound().json(json!({
+            "error": e
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Remove a bookmark
+#[utoipa::path(
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 204, description = "Bookmark removed successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[delete("/posts/{id}/bookmark")]
+pub async fn remove_bookmark(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        diesel::delete(
+            bookmarks::table
+                .filter(bookmarks::user_id.eq(user_id))
+                .filter(bookmarks::post_id.eq(post_id)),
+        )
+        .execute(&mut conn)
+        .map_err(|_| "Failed to remove bookmark")?;
+
+        Ok::<_, &'static str>(())
+    })
+    .await;
+
+    match result {
+        Ok(Ok(())) => HttpResponse::NoContent().finish(),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Get the bookmarked posts of the current user
+#[utoipa::path(
+    params(BookmarkQuery, Pagination, LanguageFilter),
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Bookmarked posts, most recent bookmark first", body = Vec<PostView>),
+        (status = 400, description = "Unknown language code"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Folder not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[get("/bookmarks")]
+pub async fn get_bookmarks(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    views: web::Data<ViewRecorder>,
+    query: web::Query<BookmarkQuery>,
+    page: web::Query<Pagination>,
+    filter: web::Query<LanguageFilter>,
+) -> impl Responder {
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let languages = match filter.languages() {
+        Ok(languages) => languages,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(json!({
+                "error": e
+            }));
+        }
+    };
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let mut bookmarked = bookmarks::table
+            .inner_join(posts::table)
+            .filter(Post::is_live())
+            .filter(Post::is_visible_to(user_id))
+            .filter(bookmarks::user_id.eq(user_id))
+            .into_boxed();
+
+        if let Some(folder_id) = query.folder_id {
+            if !folder_belongs_to(&mut conn, folder_id, user_id)? {
+                return Ok::<_, &'static str>(None);
+            }
+            bookmarked = bookmarked.filter(bookmarks::folder_id.eq(folder_id));
+        }
+        if let Some(languages) = languages {
+            bookmarked = bookmarked.filter(Post::is_in_languages(languages, false));
+        }
+
+        let bookmarked_posts = bookmarked
+            .order(bookmarks::created_at.desc())
+            .limit(page.limit())
+            .offset(page.offset())
+            .select(posts::all_columns)
+            .load::<Post>(&mut conn)
+            .map_err(|_| "Failed to load bookmarks")?;
+
+        let post_views = PostView::load_many(&mut conn, user_id, bookmarked_posts)
+            .map_err(|_| "Failed to load post details")?;
+
+        Ok(Some(post_views))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Some(post_views))) => {
+            views.record_impressions(user_id, &post_views);
+            HttpResponse::Ok().json(post_views)
+        }
+        Ok(Ok(None)) => HttpResponse::NotFound().json(json!({
+            "error": "Folder not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Get the bookmark folders of the current user
+#[utoipa::path(
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Bookmark folders", body = Vec<BookmarkFolder>),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[get("/bookmarks/folders")]
+pub async fn get_bookmark_folders(req: HttpRequest, pool: web::Data<DbPool>) -> impl Responder {
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let folders = bookmark_folders::table
+            .filter(bookmark_folders::user_id.eq(user_id))
+            .order(bookmark_folders::name.asc())
+            .load::<BookmarkFolder>(&mut conn)
+            .map_err(|_| "Failed to load folders")?;
+
+        Ok::<_, &'static str>(folders)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(folders)) => HttpResponse::Ok().json(folders),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Create a bookmark folder
+#[utoipa::path(
+    request_body = CreateBookmarkFolderRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 201, description = "Folder created successfully", body = BookmarkFolder),
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[post("/bookmarks/folders")]
+pub async fn create_bookmark_folder(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    folder_req: web::Json<CreateBookmarkFolderRequest>,
+) -> impl Responder {
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let name = folder_req.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_FOLDER_NAME_LENGTH {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "Folder name must be between 1 and 50 characters"
+        }));
+    }
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        // Folder names are unique per user
+        let folder = diesel::insert_into(bookmark_folders::table)
+            .values(&NewBookmarkFolder { user_id, name })
+            .get_result::<BookmarkFolder>(&mut conn)
+            .map_err(|_| "Failed to create folder")?;
+
+        Ok::<_, &'static str>(folder)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(folder)) => HttpResponse::Created().json(folder),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Delete a bookmark folder
+///
+/// Bookmarks filed in the folder are kept and become unfiled.
+#[utoipa::path(
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 204, description = "Folder deleted successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Folder not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[delete("/bookmarks/folders/{id}")]
+pub async fn delete_bookmark_folder(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    let folder_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let deleted = diesel::delete(
+            bookmark_folders::table
+                .find(folder_id)
+                .filter(bookmark_folders::user_id.eq(user_id)),
+        )
+        .execute(&mut conn)
+        .map_err(|_| "Failed to delete folder")?;
+
+        Ok::<_, &'static str>(deleted > 0)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(true)) => HttpResponse::NoContent().finish(),
+        Ok(Ok(false)) => HttpResponse::NotFound().json(json!({
+            "error": "Folder not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
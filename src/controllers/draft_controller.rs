@@ -0,0 +1,514 @@
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, delete, get, patch, post, web};
+use diesel::dsl::now;
+use diesel::sql_types::Bool;
+use diesel::{
+    BoolExpressionMethods, Connection, ExpressionMethods, IntoSql, OptionalExtension, QueryDsl,
+    RunQueryDsl,
+};
+use serde::{Deserialize, Deserializer};
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::controllers::post_controller::{self, CreatePostError, CreatePostRequest, PollRequest};
+use crate::models::{
+    draft::{Draft, DraftChanges, DraftPoll, NewDraft},
+    post::{ReplyAudience, Visibility},
+    post_view::PostView,
+    user::AuthedUserId,
+};
+use crate::schema::drafts;
+use crate::util::{content::ContentErrorResponse, db::DbPool, pagination::Pagination};
+
+// Drafts may go over the post length limit while being written, but not by much
+const MAX_DRAFT_LENGTH: usize = 4000;
+
+/// Used for API requests when creating a draft
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({
+    "content": "Half a thought"
+}))]
+pub struct CreateDraftRequest {
+    /// Content written so far
+    #[serde(default)]
+    pub content: String,
+    /// ID of the post to quote, if any
+    #[serde(default)]
+    pub quoted_post_id: Option<i32>,
+    /// ID of the post to reply to, if any
+    #[serde(default)]
+    pub in_reply_to_id: Option<i32>,
+    /// Who can reply to the post, defaulting to everyone
+    #[serde(default)]
+    pub reply_audience: Option<ReplyAudience>,
+    /// Who can see the post, defaulting to the `default_visibility` setting
+    #[serde(default)]
+    pub visibility: Option<Visibility>,
+    /// Warning shown instead of the content until it is expanded
+    #[serde(default)]
+    pub content_warning: Option<String>,
+    /// Whether the post is sensitive
+    #[serde(default)]
+    pub sensitive: bool,
+    /// ISO 639-1 code of the language of the post, detected when published if missing
+    #[serde(default)]
+    pub lang: Option<String>,
+    /// IDs of uploaded images to attach, in order
+    #[serde(default)]
+    pub media_ids: Vec<i32>,
+    /// Poll to attach to the post, if any
+    #[serde(default)]
+    pub poll: Option<DraftPoll>,
+}
+
+/// Used for API requests when changing a draft
+///
+/// Only the fields present are changed, so clients can autosave whatever changed.
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({
+    "content": "Half a thought, now complete",
+    "version": 3
+}))]
+pub struct UpdateDraftRequest {
+    /// New content
+    #[serde(default)]
+    pub content: Option<String>,
+    /// New post to quote, or `null` to stop quoting
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<i32>)]
+    pub quoted_post_id: Option<Option<i32>>,
+    /// New post to reply to, or `null` to stop replying
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<i32>)]
+    pub in_reply_to_id: Option<Option<i32>>,
+    /// New reply audience, or `null` for the default
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<ReplyAudience>)]
+    pub reply_audience: Option<Option<ReplyAudience>>,
+    /// New visibility, or `null` for the default
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<Visibility>)]
+    pub visibility: Option<Option<Visibility>>,
+    /// New content warning, or `null` to remove it
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<String>)]
+    pub content_warning: Option<Option<String>>,
+    /// Whether the post is sensitive
+    #[serde(default)]
+    pub sensitive: Option<bool>,
+    /// New language, or `null` to detect it when published
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<String>)]
+    pub lang: Option<Option<String>>,
+    /// New images to attach, in order
+    #[serde(default)]
+    pub media_ids: Option<Vec<i32>>,
+    /// New poll, or `null` to remove it
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<DraftPoll>)]
+    pub poll: Option<Option<DraftPoll>>,
+    /// Version of the draft the change is based on. When given, the change is refused
+    /// if the draft has been changed since, e.g. from another device
+    #[serde(default)]
+    pub version: Option<i32>,
+}
+
+// Tells a field set to `null` apart from a missing field
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+fn is_too_long(content: &str) -> bool {
+    content.chars().count() > MAX_DRAFT_LENGTH
+}
+
+/// Get the current user's drafts
+#[utoipa::path(
+    params(Pagination),
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Drafts, most recently changed first", body = Vec<Draft>),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[get("/drafts")]
+pub async fn get_drafts(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    page: web::Query<Pagination>,
+) -> impl Responder {
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let user_drafts = drafts::table
+            .filter(drafts::user_id.eq(user_id))
+            .order((drafts::updated_at.desc(), drafts::id.desc()))
+            .limit(page.limit())
+            .offset(page.offset())
+            .load::<Draft>(&mut conn)
+            .map_err(|_| "Failed to load drafts")?;
+
+        Ok::<_, &'static str>(user_drafts)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(user_drafts)) => HttpResponse::Ok().json(user_drafts),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Create a draft
+#[utoipa::path(
+    request_body = CreateDraftRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 201, description = "Draft created successfully", body = Draft),
+        (status = 400, description = "Draft is too long"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[post("/drafts")]
+pub async fn create_draft(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    draft_req: web::Json<CreateDraftRequest>,
+) -> impl Responder {
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let CreateDraftRequest {
+        content,
+        quoted_post_id,
+        in_reply_to_id,
+        reply_audience,
+        visibility,
+        content_warning,
+        sensitive,
+        lang,
+        media_ids,
+        poll,
+    } = draft_req.into_inner();
+    if is_too_long(&content) {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "Draft is too long"
+        }));
+    }
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let draft = diesel::insert_into(drafts::table)
+            .values(&NewDraft {
+                user_id,
+                content,
+                quoted_post_id,
+                in_reply_to_id,
+                reply_audience,
+                visibility,
+                content_warning,
+                sensitive,
+                lang,
+                media_ids,
+                poll_options: poll.as_ref().map(|poll| poll.options.clone()),
+                poll_duration_minutes: poll.map(|poll| poll.duration_minutes),
+            })
+            .get_result::<Draft>(&mut conn)
+            .map_err(|_| "Failed to create draft")?;
+
+        Ok::<_, &'static str>(draft)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(draft)) => HttpResponse::Created().json(draft),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Change a draft
+///
+/// Only the fields present in the request are changed. A request with a stale
+/// `version` is refused with the current draft, so the client can merge the changes.
+#[utoipa::path(
+    request_body = UpdateDraftRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Draft updated successfully", body = Draft),
+        (status = 400, description = "Draft is too long"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Draft not found"),
+        (status = 409, description = "Draft has been changed since the given version"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[patch("/drafts/{id}")]
+pub async fn update_draft(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    draft_req: web::Json<UpdateDraftRequest>,
+) -> impl Responder {
+    let draft_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let UpdateDraftRequest {
+        content,
+        quoted_post_id,
+        in_reply_to_id,
+        reply_audience,
+        visibility,
+        content_warning,
+        sensitive,
+        lang,
+        media_ids,
+        poll,
+        version,
+    } = draft_req.into_inner();
+    if content.as_deref().is_some_and(is_too_long) {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "Draft is too long"
+        }));
+    }
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let updated = diesel::update(
+            drafts::table
+                .filter(drafts::id.eq(draft_id))
+                .filter(drafts::user_id.eq(user_id))
+                .filter(
+                    drafts::version
+                        .eq(version.unwrap_or_default())
+                        .or(version.is_none().into_sql::<Bool>()),
+                ),
+        )
+        .set((
+            DraftChanges {
+                content,
+                quoted_post_id,
+                in_reply_to_id,
+                reply_audience,
+                visibility,
+                content_warning,
+                sensitive,
+                lang,
+                media_ids,
+                poll_options: poll
+                    .as_ref()
+                    .map(|poll| poll.as_ref().map(|poll| poll.options.clone())),
+                poll_duration_minutes: poll.map(|poll| poll.map(|poll| poll.duration_minutes)),
+            },
+            drafts::version.eq(drafts::version + 1),
+            drafts::updated_at.eq(now),
+        ))
+        .get_result::<Draft>(&mut conn)
+        .optional()
+        .map_err(|_| "Failed to update draft")?;
+
+        if let Some(draft) = updated {
+            return Ok(Ok(draft));
+        }
+
+        // Either the draft does not exist or its version did not match
+        let current = drafts::table
+            .filter(drafts::id.eq(draft_id))
+            .filter(drafts::user_id.eq(user_id))
+            .first::<Draft>(&mut conn)
+            .optional()
+            .map_err(|_| "Database error finding draft")?;
+
+        Ok::<_, &'static str>(Err(current))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Ok(draft))) => HttpResponse::Ok().json(draft),
+        Ok(Ok(Err(Some(current)))) => HttpResponse::Conflict().json(json!({
+            "error": "Draft has been changed since this version",
+            "draft": current
+        })),
+        Ok(Ok(Err(None))) => HttpResponse::NotFound().json(json!({
+            "error": "Draft not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Delete a draft
+#[utoipa::path(
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 204, description = "Draft deleted successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Draft not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[delete("/drafts/{id}")]
+pub async fn delete_draft(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    let draft_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let deleted = diesel::delete(
+            drafts::table
+                .filter(drafts::id.eq(draft_id))
+                .filter(drafts::user_id.eq(user_id)),
+        )
+        .execute(&mut conn)
+        .map_err(|_| "Failed to delete draft")?;
+
+        Ok::<_, &'static str>(deleted > 0)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(true)) => HttpResponse::NoContent().finish(),
+        Ok(Ok(false)) => HttpResponse::NotFound().json(json!({
+            "error": "Draft not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Publish a draft as a post
+///
+/// The draft goes through the same checks as a new post and is removed once the post
+/// is created. If the checks fail, the draft is kept.
+#[utoipa::path(
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 201, description = "Draft published successfully", body = PostView),
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Draft not found"),
+        (status = 422, description = "Invalid post content", body = ContentErrorResponse),
+        (status = 500, description = "Server error")
+    )
+)]
+#[post("/drafts/{id}/publish")]
+pub async fn publish_draft(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    let draft_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool
+            .get()
+            .map_err(|_| CreatePostError::Failed("Failed to get DB connection"))?;
+
+        // Lock the draft so that it is published only once
+        let post = conn.transaction::<_, CreatePostError, _>(|conn| {
+            let draft = drafts::table
+                .filter(drafts::id.eq(draft_id))
+                .filter(drafts::user_id.eq(user_id))
+                .for_update()
+                .first::<Draft>(conn)
+                .optional()?;
+
+            let Some(draft) = draft else {
+                return Ok(None);
+            };
+
+            let post = post_controller::insert_post(
+                conn,
+                user_id,
+                &CreatePostRequest {
+                    content: draft.content,
+                    quoted_post_id: draft.quoted_post_id,
+                    in_reply_to_id: draft.in_reply_to_id,
+                    reply_audience: draft.reply_audience,
+                    // Publishing a draft posts it now, scheduling is done with new posts
+                    publish_at: None,
+                    poll: draft.poll.map(|poll| PollRequest {
+                        options: poll.options,
+                        duration_minutes: poll.duration_minutes,
+                    }),
+                    media_ids: draft.media_ids,
+                    visibility: draft.visibility,
+                    content_warning: draft.content_warning,
+                    sensitive: draft.sensitive,
+                    lang: draft.lang,
+                },
+            )?;
+
+            diesel::delete(drafts::table.filter(drafts::id.eq(draft.id))).execute(conn)?;
+
+            Ok(Some(post))
+        })?;
+
+        match post {
+            Some(post) => PostView::load(&mut conn, user_id, post)
+                .map(Some)
+                .map_err(|_| CreatePostError::Failed("Failed to load post details")),
+            None => Ok(None),
+        }
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Some(post_view))) => HttpResponse::Created().json(post_view),
+        Ok(Ok(None)) => HttpResponse::NotFound().json(json!({
+            "error": "Draft not found"
+        })),
+        Ok(Err(e)) => e.into_response(),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
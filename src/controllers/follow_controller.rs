@@ -0,0 +1,133 @@
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, delete, post, web};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use serde_json::json;
+
+use crate::models::{
+    follow::NewFollow,
+    user::{AuthedUserId, User},
+};
+use crate::schema::{follows, users};
+use crate::util::db::DbPool;
+
+/// Follow a user
+///
+/// Followers can see the user's followers-only posts.
+#[utoipa::path(
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 204, description = "User followed successfully"),
+        (status = 400, description = "Users cannot follow themselves"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "User not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[post("/users/{id}/follow")]
+pub async fn follow_user(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    let followee_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    if followee_id == user_id {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "You cannot follow yourself"
+        }));
+    }
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        // Check if user exists
+        let user_exists = users::table
+            .find(followee_id)
+            .first::<User>(&mut conn)
+            .optional()
+            .map_err(|_| "Database error checking user")?;
+
+        if user_exists.is_none() {
+            return Ok::<_, &'static str>(false);
+        }
+
+        // Following an already followed user is a no-op
+        diesel::insert_into(follows::table)
+            .values(&NewFollow {
+                follower_id: user_id,
+                followee_id,
+            })
+            .on_conflict_do_nothing()
+            .execute(&mut conn)
+            .map_err(|_| "Failed to follow user")?;
+
+        Ok(true)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(true)) => HttpResponse::NoContent().finish(),
+        Ok(Ok(false)) => HttpResponse::NotFound().json(json!({
+            "error": "User not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Unfollow a user
+#[utoipa::path(
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 204, description = "User unfollowed successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[delete("/users/{id}/follow")]
+pub async fn unfollow_user(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    let followee_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        // Unfollowing a user who is not followed is a no-op
+        diesel::delete(
+            follows::table
+                .filter(follows::follower_id.eq(user_id))
+                .filter(follows::followee_id.eq(followee_id)),
+        )
+        .execute(&mut conn)
+        .map_err(|_| "Failed to unfollow user")?;
+
+        Ok::<_, &'static str>(())
+    })
+    .await;
+
+    match result {
+        Ok(Ok(())) => HttpResponse::NoContent().finish(),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
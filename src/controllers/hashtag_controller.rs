@@ -0,0 +1,93 @@
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, get, web};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use serde_json::json;
+
+use crate::models::{post::Post, post_view::PostView, user::AuthedUserId};
+use crate::schema::{hashtags, post_hashtags, posts};
+use crate::util::{
+    db::DbPool, entities, language::LanguageFilter, pagination::Pagination, views::ViewRecorder,
+};
+
+/// Get the posts containing a hashtag
+#[utoipa::path(
+    params(
+        ("tag" = String, Path, description = "Hashtag, with or without the leading `#`"),
+        Pagination,
+        LanguageFilter
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Posts with the hashtag, most recent first", body = Vec<PostView>),
+        (status = 400, description = "Unknown language code"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[get("/hashtags/{tag}/posts")]
+pub async fn get_hashtag_posts(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    views: web::Data<ViewRecorder>,
+    tag: web::Path<String>,
+    page: web::Query<Pagination>,
+    filter: web::Query<LanguageFilter>,
+) -> impl Responder {
+    let name = entities::normalize_hashtag(&tag.into_inner());
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let languages = match filter.languages() {
+        Ok(languages) => languages,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(json!({
+                "error": e
+            }));
+        }
+    };
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        // Unknown hashtags simply have no posts
+        let mut tagged = post_hashtags::table
+            .inner_join(hashtags::table)
+            .inner_join(posts::table)
+            .filter(hashtags::name.eq(&name))
+            .filter(Post::is_live())
+            .filter(Post::is_listed_for(user_id))
+            .into_boxed();
+        if let Some(languages) = languages {
+            tagged = tagged.filter(Post::is_in_languages(languages, false));
+        }
+
+        let tagged_posts = tagged
+            .order(posts::created_at.desc())
+            .limit(page.limit())
+            .offset(page.offset())
+            .select(posts::all_columns)
+            .load::<Post>(&mut conn)
+            .map_err(|_| "Failed to load posts")?;
+
+        let post_views = PostView::load_many(&mut conn, user_id, tagged_posts)
+            .map_err(|_| "Failed to load post details")?;
+
+        Ok::<_, &'static str>(post_views)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(post_views)) => {
+            views.record_impressions(user_id, &post_views);
+            HttpResponse::Ok().json(post_views)
+        }
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
@@ -0,0 +1,285 @@
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, delete, get, post, web};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use serde_json::json;
+
+use crate::models::{
+    like::NewLike,
+    post::Post,
+    post_view::PostView,
+    user::{AuthedUserId, User},
+};
+use crate::schema::{likes, posts, users};
+use crate::util::{
+    db::DbPool, language::LanguageFilter, pagination::Pagination, views::ViewRecorder,
+};
+
+/// Like a post
+#[utoipa::path(
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 204, description = "Post liked successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Post not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[post("/posts/{id}/like")]
+pub async fn like_post(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        // Check if post exists
+        let post_exists = Post::visible_to(user_id)
+            .filter(posts::id.eq(post_id))
+            .first::<Post>(&mut conn)
+            .optional()
+            .map_err(|_| "Database error checking post")?;
+
+        if post_exists.is_none() {
+            return Ok::<_, &'static str>(false);
+        }
+
+        // Liking an already liked post is a no-op
+        diesel::insert_into(likes::table)
+            .values(&NewLike { user_id, post_id })
+            .on_conflict_do_nothing()
+            .execute(&mut conn)
+            .map_err(|_| "Failed to like post")?;
+
+        Ok(true)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(true)) => HttpResponse::NoContent().finish(),
+        Ok(Ok(false)) => HttpResponse::NotFound().json(json!({
+            "error": "Post not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Remove a like from a post
+#[utoipa::path(
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 204, description = "Like removed successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[delete("/posts/{id}/like")]
+pub async fn unlike_post(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        // Unliking a post that is not liked is a no-op
+        diesel::delete(
+            likes::table
+                .filter(likes::user_id.eq(user_id))
+                .filter(likes::post_id.eq(post_id)),
+        )
+        .execute(&mut conn)
+        .map_err(|_| "Failed to remove like")?;
+
+        Ok::<_, &'static str>(())
+    })
+    .await;
+
+    match result {
+        Ok(Ok(())) => HttpResponse::NoContent().finish(),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Get the users who liked a post
+#[utoipa::path(
+    params(Pagination),
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Users who liked the post, most recent first", body = Vec<User>),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Post not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[get("/posts/{id}/likes")]
+pub async fn get_post_likes(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    page: web::Query<Pagination>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        // Check if post exists
+        let post_exists = Post::visible_to(user_id)
+            .filter(posts::id.eq(post_id))
+            .first::<Post>(&mut conn)
+            .optional()
+            .map_err(|_| "Database error checking post")?;
+
+        if post_exists.is_none() {
+            return Ok::<_, &'static str>(None);
+        }
+
+        let likers = likes::table
+            .inner_join(users::table)
+            .filter(likes::post_id.eq(post_id))
+            .order(likes::created_at.desc())
+            .limit(page.limit())
+            .offset(page.offset())
+            .select(users::all_columns)
+            .load::<User>(&mut conn)
+            .map_err(|_| "Failed to load likes")?;
+
+        Ok(Some(likers))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Some(likers))) => HttpResponse::Ok().json(likers),
+        Ok(Ok(None)) => HttpResponse::NotFound().json(json!({
+            "error": "Post not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Get the posts a user liked
+#[utoipa::path(
+    params(Pagination, LanguageFilter),
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Posts liked by the user, most recent like first", body = Vec<PostView>),
+        (status = 400, description = "Unknown language code"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "User not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[get("/users/{id}/likes")]
+pub async fn get_user_likes(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    views: web::Data<ViewRecorder>,
+    id: web::Path<i32>,
+    page: web::Query<Pagination>,
+    filter: web::Query<LanguageFilter>,
+) -> impl Responder {
+    let liker_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let languages = match filter.languages() {
+        Ok(languages) => languages,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(json!({
+                "error": e
+            }));
+        }
+    };
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        // Check if user exists
+        let user_exists = users::table
+            .find(liker_id)
+            .first::<User>(&mut conn)
+            .optional()
+            .map_err(|_| "Database error checking user")?;
+
+        if user_exists.is_none() {
+            return Ok::<_, &'static str>(None);
+        }
+
+        let mut liked = likes::table
+            .inner_join(posts::table)
+            .filter(Post::is_live())
+            .filter(Post::is_visible_to(user_id))
+            .filter(likes::user_id.eq(liker_id))
+            .into_boxed();
+        if let Some(languages) = languages {
+            liked = liked.filter(Post::is_in_languages(languages, false));
+        }
+
+        let liked_posts = liked
+            .order(likes::created_at.desc())
+            .limit(page.limit())
+            .offset(page.offset())
+            .select(posts::all_columns)
+            .load::<Post>(&mut conn)
+            .map_err(|_| "Failed to load likes")?;
+
+        let post_views = PostView::load_many(&mut conn, user_id, liked_posts)
+            .map_err(|_| "Failed to load post details")?;
+
+        Ok(Some(post_views))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Some(post_views))) => {
+            views.record_impressions(user_id, &post_views);
+            HttpResponse::Ok().json(post_views)
+        }
+        Ok(Ok(None)) => HttpResponse::NotFound().json(json!({
+            "error": "User not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
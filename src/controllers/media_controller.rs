@@ -0,0 +1,341 @@
+use actix_multipart::MultipartError;
+use actix_multipart::form::{MultipartForm, bytes::Bytes, text::Text};
+use actix_web::error::{InternalError, PayloadError};
+use actix_web::{
+    HttpMessage, HttpRequest, HttpResponse, Responder, get, http::header, patch, post, web,
+};
+use diesel::{Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::{
+    media::{Media, MediaVariant, MediaView, NewMedia},
+    user::AuthedUserId,
+};
+use crate::schema::{media, media_variants};
+use crate::storage::{self, MediaStore};
+use crate::util::{db::DbPool, images};
+
+const MAX_ALT_TEXT_LENGTH: usize = 1500;
+
+/// Used for API requests when uploading an image
+#[derive(MultipartForm, ToSchema)]
+pub struct UploadMediaForm {
+    /// The image: JPEG, PNG, GIF or WebP, at most `MEDIA_MAX_UPLOAD_BYTES`
+    #[schema(value_type = String, format = Binary)]
+    pub file: Bytes,
+    /// Description of the image for people who cannot see it, at most 1500 characters
+    #[schema(value_type = Option<String>)]
+    pub alt_text: Option<Text<String>>,
+}
+
+/// Used for API requests when changing an uploaded image
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({
+    "alt_text": "A cat asleep on a keyboard"
+}))]
+pub struct UpdateMediaRequest {
+    /// Description of the image for people who cannot see it, at most 1500
+    /// characters, or `null` to remove it
+    #[serde(default)]
+    pub alt_text: Option<String>,
+}
+
+// Trims alt text, dropping it when blank
+fn normalize_alt_text(alt_text: Option<String>) -> Result<Option<String>, String> {
+    let alt_text = alt_text
+        .map(|alt_text| alt_text.trim().to_string())
+        .filter(|alt_text| !alt_text.is_empty());
+    if alt_text
+        .as_ref()
+        .is_some_and(|alt_text| alt_text.chars().count() > MAX_ALT_TEXT_LENGTH)
+    {
+        return Err(format!(
+            "Alt text must be at most {} characters",
+            MAX_ALT_TEXT_LENGTH
+        ));
+    }
+    Ok(alt_text)
+}
+
+/// Turns upload errors into JSON responses, telling apart files that are too large
+pub fn upload_error(error: MultipartError, _req: &HttpRequest) -> actix_web::Error {
+    let response = match &error {
+        MultipartError::Payload(PayloadError::Overflow) => {
+            HttpResponse::PayloadTooLarge().json(json!({
+                "error": "File is too large"
+            }))
+        }
+        _ => HttpResponse::BadRequest().json(json!({
+            "error": error.to_string()
+        })),
+    };
+    InternalError::from_response(error, response).into()
+}
+
+/// Upload an image
+///
+/// The format is detected from the content of the file, not its name. The image is
+/// re-encoded without its metadata, such as EXIF and XMP data, after being turned
+/// upright: PNG is stored for anything but JPEG, and animations keep their first
+/// frame. The returned ID can then be given in `media_ids` when creating a post.
+#[utoipa::path(
+    request_body(content = UploadMediaForm, content_type = "multipart/form-data"),
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 201, description = "Image uploaded successfully", body = MediaView),
+        (status = 400, description = "Invalid upload or alt text"),
+        (status = 401, description = "Unauthorized"),
+        (status = 413, description = "File is too large"),
+        (status = 415, description = "Unsupported image format"),
+        (status = 422, description = "Image could not be decoded"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[post("/media")]
+pub async fn upload_media(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    store: web::Data<dyn MediaStore>,
+    MultipartForm(form): MultipartForm<UploadMediaForm>,
+) -> impl Responder {
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let alt_text = match normalize_alt_text(form.alt_text.map(|alt_text| alt_text.into_inner())) {
+        Ok(alt_text) => alt_text,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(json!({
+                "error": e
+            }));
+        }
+    };
+
+    let bytes = form.file.data;
+    let Some(format) = images::detect_format(&bytes) else {
+        return HttpResponse::UnsupportedMediaType().json(json!({
+            "error": "Unsupported image format, use JPEG, PNG, GIF or WebP"
+        }));
+    };
+
+    // Use a web::block to offload image processing and storage to a separate thread
+    let result = web::block(move || {
+        let processed = match images::process(&bytes, format) {
+            Ok(processed) => processed,
+            Err(e) => return Ok(Err(e)),
+        };
+
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        // Random keys, so that URLs cannot be guessed
+        let prefix = format!("media/{}", Uuid::new_v4());
+        // The upload itself is not stored, only its copy without metadata
+        let original_key = format!("{}/original.{}", prefix, processed.format.extension);
+        let mut files = vec![(
+            original_key.clone(),
+            processed.format.content_type,
+            processed.bytes.clone(),
+        )];
+        files.extend(processed.variants.iter().map(|variant| {
+            (
+                format!("{}/{}.{}", prefix, variant.name, variant.format.extension),
+                variant.format.content_type,
+                variant.bytes.clone(),
+            )
+        }));
+
+        let stored = files
+            .iter()
+            .try_for_each(|(key, content_type, file)| store.put(key, content_type, file));
+
+        let saved = stored.map_err(|_| "Failed to store media").and_then(|_| {
+            conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                let media = diesel::insert_into(media::table)
+                    .values(&NewMedia {
+                        user_id,
+                        content_type: processed.format.content_type.to_string(),
+                        width: processed.width as i32,
+                        height: processed.height as i32,
+                        size_bytes: processed.bytes.len() as i32,
+                        storage_key: original_key,
+                        blurhash: processed.blurhash.clone(),
+                        dominant_color: processed.dominant_color.clone(),
+                        alt_text,
+                    })
+                    .get_result::<Media>(conn)?;
+
+                let variants = processed
+                    .variants
+                    .iter()
+                    .zip(files.iter().skip(1))
+                    .map(|(variant, (key, _, _))| MediaVariant {
+                        media_id: media.id,
+                        name: variant.name.to_string(),
+                        width: variant.width as i32,
+                        height: variant.height as i32,
+                        storage_key: key.clone(),
+                    })
+                    .collect::<Vec<_>>();
+                diesel::insert_into(media_variants::table)
+                    .values(&variants)
+                    .execute(conn)?;
+
+                Ok(MediaView::new(media, variants))
+            })
+            .map_err(|_| "Failed to save media")
+        });
+
+        // Do not leave files behind when the upload fails halfway
+        if saved.is_err() {
+            for (key, _, _) in &files {
+                let _ = store.delete(key);
+            }
+        }
+
+        Ok::<_, &'static str>(Ok(saved?))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Ok(media_view))) => HttpResponse::Created().json(media_view),
+        Ok(Ok(Err(e))) => HttpResponse::UnprocessableEntity().json(json!({
+            "error": e
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Change an uploaded image
+///
+/// Alt text can be changed at any time, including after the image is posted and
+/// after the post can no longer be edited.
+#[utoipa::path(
+    request_body = UpdateMediaRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Image updated successfully", body = MediaView),
+        (status = 400, description = "Alt text is too long"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Media not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[patch("/media/{id}")]
+pub async fn update_media(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    media_req: web::Json<UpdateMediaRequest>,
+) -> impl Responder {
+    let media_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let alt_text = match normalize_alt_text(media_req.into_inner().alt_text) {
+        Ok(alt_text) => alt_text,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(json!({
+                "error": e
+            }));
+        }
+    };
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let updated = diesel::update(
+            media::table
+                .filter(media::id.eq(media_id))
+                .filter(media::user_id.eq(user_id)),
+        )
+        .set(media::alt_text.eq(alt_text))
+        .get_result::<Media>(&mut conn)
+        .optional()
+        .map_err(|_| "Failed to update media")?;
+
+        let Some(media) = updated else {
+            return Ok(None);
+        };
+
+        let variants = media_variants::table
+            .filter(media_variants::media_id.eq(media.id))
+            .load::<MediaVariant>(&mut conn)
+            .map_err(|_| "Failed to load media variants")?;
+
+        Ok::<_, &'static str>(Some(MediaView::new(media, variants)))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Some(media_view))) => HttpResponse::Ok().json(media_view),
+        Ok(Ok(None)) => HttpResponse::NotFound().json(json!({
+            "error": "Media not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Get an uploaded image file
+///
+/// Public, so that clients can use media URLs directly. Keys are random and
+/// cannot be guessed.
+#[utoipa::path(
+    params(
+        ("key" = String, Path, description = "Key of the file, as found in media URLs")
+    ),
+    responses(
+        (status = 200, description = "The image file"),
+        (status = 404, description = "File not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[get("/media/files/{key:.*}")]
+pub async fn get_media_file(
+    store: web::Data<dyn MediaStore>,
+    key: web::Path<String>,
+) -> impl Responder {
+    let key = key.into_inner();
+    let content_type = images::content_type_of(&key).filter(|_| storage::is_valid_key(&key));
+    let Some(content_type) = content_type else {
+        return HttpResponse::NotFound().json(json!({
+            "error": "File not found"
+        }));
+    };
+
+    // Use a web::block to offload storage access to a separate thread
+    let result = web::block(move || store.get(&key)).await;
+
+    match result {
+        Ok(Ok(Some(bytes))) => HttpResponse::Ok()
+            .content_type(content_type)
+            // Files never change once stored
+            .insert_header((header::CACHE_CONTROL, "public, max-age=31536000, immutable"))
+            .insert_header((header::X_CONTENT_TYPE_OPTIONS, "nosniff"))
+            .body(bytes),
+        Ok(Ok(None)) => HttpResponse::NotFound().json(json!({
+            "error": "File not found"
+        })),
+        Ok(Err(_)) => HttpResponse::InternalServerError().json(json!({
+            "error": "Failed to read media"
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
@@ -0,0 +1,83 @@
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, get, web};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use serde_json::json;
+
+use crate::models::{post::Post, post_view::PostView, user::AuthedUserId};
+use crate::schema::{post_mentions, posts};
+use crate::util::{
+    db::DbPool, language::LanguageFilter, pagination::Pagination, views::ViewRecorder,
+};
+
+/// Get the posts mentioning the current user
+#[utoipa::path(
+    params(Pagination, LanguageFilter),
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Posts mentioning the current user, most recent first", body = Vec<PostView>),
+        (status = 400, description = "Unknown language code"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[get("/users/me/mentions")]
+pub async fn get_my_mentions(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    views: web::Data<ViewRecorder>,
+    page: web::Query<Pagination>,
+    filter: web::Query<LanguageFilter>,
+) -> impl Responder {
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let languages = match filter.languages() {
+        Ok(languages) => languages,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(json!({
+                "error": e
+            }));
+        }
+    };
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let mut mentioning = post_mentions::table
+            .inner_join(posts::table)
+            .filter(post_mentions::user_id.eq(user_id))
+            .filter(Post::is_live())
+            .into_boxed();
+        if let Some(languages) = languages {
+            mentioning = mentioning.filter(Post::is_in_languages(languages, false));
+        }
+
+        let mentioning_posts = mentioning
+            .order(posts::created_at.desc())
+            .limit(page.limit())
+            .offset(page.offset())
+            .select(posts::all_columns)
+            .load::<Post>(&mut conn)
+            .map_err(|_| "Failed to load mentions")?;
+
+        let post_views = PostView::load_many(&mut conn, user_id, mentioning_posts)
+            .map_err(|_| "Failed to load post details")?;
+
+        Ok::<_, &'static str>(post_views)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(post_views)) => {
+            views.record_impressions(user_id, &post_views);
+            HttpResponse::Ok().json(post_views)
+        }
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
@@ -1,3 +1,20 @@
 // Export controller functions
+pub mod analytics_controller;
 pub mod auth_controller;
+pub mod bookmark_controller;
+pub mod draft_controller;
+pub mod follow_controller;
+pub mod hashtag_controller;
+pub mod like_controller;
+pub mod media_controller;
+pub mod mention_controller;
+pub mod moderation_controller;
+pub mod pin_controller;
+pub mod poll_controller;
 pub mod post_controller;
+pub mod repost_controller;
+pub mod scheduled_post_controller;
+pub mod settings_controller;
+pub mod thread_controller;
+pub mod trend_controller;
+pub mod user_controller;
@@ -0,0 +1,168 @@
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, get, put, web};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::models::{
+    post::Post,
+    post_view::PostView,
+    user::{AuthedUserId, User},
+};
+use crate::schema::posts;
+use crate::util::{db::DbPool, pagination::Pagination};
+
+/// Get deleted posts that have not been purged yet
+#[utoipa::path(
+    params(Pagination),
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Deleted posts, most recently deleted first", body = Vec<Post>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Moderator access required"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[get("/moderation/posts/deleted")]
+pub async fn get_deleted_posts(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    page: web::Query<Pagination>,
+) -> impl Responder {
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let is_moderator = User::moderator_status(&mut conn, user_id)
+            .map_err(|_| "Database error checking user")?;
+
+        if !is_moderator {
+            return Ok::<_, &'static str>(None);
+        }
+
+        let tombstones = posts::table
+            .filter(posts::deleted_at.is_not_null())
+            .order(posts::deleted_at.desc())
+            .limit(page.limit())
+            .offset(page.offset())
+            .load::<Post>(&mut conn)
+            .map_err(|_| "Failed to load deleted posts")?;
+
+        Ok(Some(tombstones))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Some(tombstones))) => HttpResponse::Ok().json(tombstones),
+        Ok(Ok(None)) => HttpResponse::Forbidden().json(json!({
+            "error": "Moderator access required"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Reasons for refusing a moderation action
+enum Refusal {
+    /// The user is not a moderator
+    NotModerator,
+    /// The post does not exist
+    NotFound,
+}
+
+/// Used for API requests when marking a post as sensitive
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({
+    "sensitive": true
+}))]
+pub struct MarkSensitiveRequest {
+    /// Whether the post is sensitive, whatever its author says
+    pub sensitive: bool,
+}
+
+/// Mark a post as sensitive
+///
+/// Moderators can mark anyone's post as sensitive, which its author cannot undo, and
+/// lift the mark again.
+#[utoipa::path(
+    request_body = MarkSensitiveRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Post marked successfully", body = PostView),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Moderator access required"),
+        (status = 404, description = "Post not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[put("/moderation/posts/{id}/sensitive")]
+pub async fn mark_post_sensitive(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    mark_req: web::Json<MarkSensitiveRequest>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let sensitive = mark_req.sensitive;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let is_moderator = User::moderator_status(&mut conn, user_id)
+            .map_err(|_| "Database error checking user")?;
+
+        if !is_moderator {
+            return Ok::<_, &'static str>(Err(Refusal::NotModerator));
+        }
+
+        let updated_post = diesel::update(
+            posts::table
+                .filter(posts::id.eq(post_id))
+                .filter(posts::deleted_at.is_null()),
+        )
+        .set(posts::sensitive_by_moderator.eq(sensitive))
+        .get_result::<Post>(&mut conn)
+        .optional()
+        .map_err(|_| "Failed to update post")?;
+
+        let Some(updated_post) = updated_post else {
+            return Ok(Err(Refusal::NotFound));
+        };
+
+        let post_view = PostView::load(&mut conn, user_id, updated_post)
+            .map_err(|_| "Failed to load post details")?;
+
+        Ok(Ok(post_view))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Ok(post_view))) => HttpResponse::Ok().json(post_view),
+        Ok(Ok(Err(Refusal::NotModerator))) => HttpResponse::Forbidden().json(json!({
+            "error": "Moderator access required"
+        })),
+        Ok(Ok(Err(Refusal::NotFound))) => HttpResponse::NotFound().json(json!({
+            "error": "Post not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
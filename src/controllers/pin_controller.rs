@@ -0,0 +1,281 @@
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, delete, post, put, web};
+use diesel::{Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::models::{
+    pinned_post::{self, NewPinnedPost},
+    post::{Post, Visibility},
+    post_view::PostView,
+    user::AuthedUserId,
+};
+use crate::schema::{pinned_posts, posts, users};
+use crate::util::{config, db::DbPool};
+
+/// Reasons for refusing a change to pinned posts
+enum Refusal {
+    /// The post does not exist or belongs to someone else
+    NotFound,
+    /// The post cannot be pinned
+    Forbidden(&'static str),
+    /// The request does not match the pinned posts
+    Invalid(&'static str),
+}
+
+/// Pin a post to the current user's profile
+///
+/// Only public posts can be pinned, up to `PROFILE_MAX_PINNED_POSTS` of them. The
+/// newest pin is shown first. Posts are unpinned when they are deleted or stop being
+/// public.
+#[utoipa::path(
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 204, description = "Post pinned successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Post cannot be pinned"),
+        (status = 404, description = "Post not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[post("/posts/{id}/pin")]
+pub async fn pin_post(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let max_pins = config::env_or("PROFILE_MAX_PINNED_POSTS", 3);
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            // Lock the user so that concurrent pins cannot exceed the limit
+            users::table
+                .find(user_id)
+                .select(users::id)
+                .for_update()
+                .first::<i32>(conn)?;
+
+            // Users can only pin their own published posts. The post is locked so that
+            // it cannot be deleted or made private while it is being pinned.
+            let post = posts::table
+                .filter(Post::is_live())
+                .filter(posts::id.eq(post_id))
+                .filter(posts::user_id.eq(user_id))
+                .for_update()
+                .first::<Post>(conn)
+                .optional()?;
+
+            let Some(post) = post else {
+                return Ok(Err(Refusal::NotFound));
+            };
+            if post.visibility != Visibility::Public {
+                return Ok(Err(Refusal::Forbidden("Only public posts can be pinned")));
+            }
+
+            let pins = pinned_posts::table
+                .filter(pinned_posts::user_id.eq(user_id))
+                .order(pinned_posts::position.asc())
+                .select((pinned_posts::post_id, pinned_posts::position))
+                .load::<(i32, i32)>(conn)?;
+
+            // Pinning a pinned post again is a no-op
+            if pins.iter().any(|(pinned_id, _)| *pinned_id == post_id) {
+                return Ok(Ok(()));
+            }
+            if pins.len() >= max_pins {
+                return Ok(Err(Refusal::Forbidden(
+                    "Maximum number of pinned posts reached",
+                )));
+            }
+
+            // New pins go first
+            let position = pins.first().map_or(0, |(_, position)| position - 1);
+            diesel::insert_into(pinned_posts::table)
+                .values(&NewPinnedPost {
+                    post_id,
+                    user_id,
+                    position,
+                })
+                .execute(conn)?;
+
+            Ok(Ok(()))
+        })
+        .map_err(|_| "Failed to pin post")
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Ok(()))) => HttpResponse::NoContent().finish(),
+        Ok(Ok(Err(Refusal::NotFound))) => HttpResponse::NotFound().json(json!({
+            "error": "Post not found"
+        })),
+        Ok(Ok(Err(Refusal::Forbidden(e)))) => HttpResponse::Forbidden().json(json!({
+            "error": e
+        })),
+        Ok(Ok(Err(Refusal::Invalid(e)))) => HttpResponse::BadRequest().json(json!({
+            "error": e
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Unpin a post from the current user's profile
+#[utoipa::path(
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 204, description = "Post unpinned successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[delete("/posts/{id}/pin")]
+pub async fn unpin_post(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        // Unpinning a post that is not pinned is a no-op
+        diesel::delete(
+            pinned_posts::table
+                .filter(pinned_posts::post_id.eq(post_id))
+                .filter(pinned_posts::user_id.eq(user_id)),
+        )
+        .execute(&mut conn)
+        .map_err(|_| "Failed to unpin post")?;
+
+        Ok::<_, &'static str>(())
+    })
+    .await;
+
+    match result {
+        Ok(Ok(())) => HttpResponse::NoContent().finish(),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Used for API requests when reordering pinned posts
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({
+    "post_ids": [12, 3]
+}))]
+pub struct ReorderPinsRequest {
+    /// IDs of all pinned posts, in the order they should be shown
+    pub post_ids: Vec<i32>,
+}
+
+/// Reorder the current user's pinned posts
+#[utoipa::path(
+    request_body = ReorderPinsRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Pinned posts in their new order", body = Vec<PostView>),
+        (status = 400, description = "The IDs are not those of the pinned posts"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[put("/users/me/pins")]
+pub async fn reorder_pins(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    pins_req: web::Json<ReorderPinsRequest>,
+) -> impl Responder {
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let post_ids = pins_req.into_inner().post_ids;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let reordered = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                let mut pinned_ids = pinned_posts::table
+                    .filter(pinned_posts::user_id.eq(user_id))
+                    .select(pinned_posts::post_id)
+                    .for_update()
+                    .load::<i32>(conn)?;
+
+                // Every pinned post must be listed exactly once
+                let mut requested_ids = post_ids.clone();
+                pinned_ids.sort_unstable();
+                requested_ids.sort_unstable();
+                if pinned_ids != requested_ids {
+                    return Ok(Err(Refusal::Invalid(
+                        "post_ids must list each pinned post exactly once",
+                    )));
+                }
+
+                for (position, post_id) in post_ids.iter().enumerate() {
+                    diesel::update(pinned_posts::table.find(post_id))
+                        .set(pinned_posts::position.eq(position as i32))
+                        .execute(conn)?;
+                }
+
+                Ok(Ok(()))
+            })
+            .map_err(|_| "Failed to reorder pinned posts")?;
+
+        if let Err(refusal) = reordered {
+            return Ok::<_, &'static str>(Err(refusal));
+        }
+
+        let pinned = pinned_post::load_posts(&mut conn, user_id, user_id)
+            .map_err(|_| "Failed to load pinned posts")?;
+        let post_views = PostView::load_many(&mut conn, user_id, pinned)
+            .map_err(|_| "Failed to load post details")?;
+
+        Ok(Ok(post_views))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Ok(post_views))) => HttpResponse::Ok().json(post_views),
+        Ok(Ok(Err(Refusal::Invalid(e)))) => HttpResponse::BadRequest().json(json!({
+            "error": e
+        })),
+        Ok(Ok(Err(Refusal::NotFound))) => HttpResponse::NotFound().json(json!({
+            "error": "Post not found"
+        })),
+        Ok(Ok(Err(Refusal::Forbidden(e)))) => HttpResponse::Forbidden().json(json!({
+            "error": e
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
@@ -0,0 +1,145 @@
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, post, web};
+use chrono::NaiveDateTime;
+use diesel::dsl::now;
+use diesel::upsert::excluded;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::models::{
+    poll::{NewPollVote, Poll, PollView},
+    post::Post,
+    user::AuthedUserId,
+};
+use crate::schema::{poll_options, poll_votes, polls, posts};
+use crate::util::db::DbPool;
+
+/// Used for API requests when voting in a poll
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({
+    "option_id": 2
+}))]
+pub struct VoteRequest {
+    /// ID of the chosen option
+    pub option_id: i32,
+}
+
+/// Reasons for refusing a vote
+enum Refusal {
+    /// The post does not exist or has no poll
+    NotFound,
+    /// The option is not part of the poll
+    Invalid(&'static str),
+    /// The poll does not accept votes anymore
+    Forbidden(&'static str),
+}
+
+/// Vote in the poll of a post
+///
+/// Each user has a single vote per poll, which can be changed until the poll closes.
+#[utoipa::path(
+    request_body = VoteRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Vote recorded successfully", body = PollView),
+        (status = 400, description = "Option is not part of the poll"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Poll is closed"),
+        (status = 404, description = "Poll not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[post("/posts/{id}/poll/votes")]
+pub async fn vote_in_poll(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    vote_req: web::Json<VoteRequest>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+    let option_id = vote_req.option_id;
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let post = Post::visible_to(user_id)
+            .filter(posts::id.eq(post_id))
+            .first::<Post>(&mut conn)
+            .optional()
+            .map_err(|_| "Database error finding post")?;
+        let poll = polls::table
+            .find(post_id)
+            .first::<Poll>(&mut conn)
+            .optional()
+            .map_err(|_| "Database error finding poll")?;
+
+        let (Some(post), Some(poll)) = (post, poll) else {
+            return Ok::<_, &'static str>(Err(Refusal::NotFound));
+        };
+
+        let current_time = diesel::select(now)
+            .get_result::<NaiveDateTime>(&mut conn)
+            .map_err(|_| "Database error checking poll")?;
+        if poll.closes_at(&post) <= current_time {
+            return Ok(Err(Refusal::Forbidden("Poll is closed")));
+        }
+
+        let option_exists = poll_options::table
+            .filter(poll_options::id.eq(option_id))
+            .filter(poll_options::post_id.eq(post_id))
+            .select(poll_options::id)
+            .first::<i32>(&mut conn)
+            .optional()
+            .map_err(|_| "Database error checking option")?;
+        if option_exists.is_none() {
+            return Ok(Err(Refusal::Invalid("Option is not part of the poll")));
+        }
+
+        // Voting again changes the vote
+        diesel::insert_into(poll_votes::table)
+            .values(&NewPollVote {
+                post_id,
+                user_id,
+                option_id,
+            })
+            .on_conflict((poll_votes::post_id, poll_votes::user_id))
+            .do_update()
+            .set((
+                poll_votes::option_id.eq(excluded(poll_votes::option_id)),
+                poll_votes::voted_at.eq(now),
+            ))
+            .execute(&mut conn)
+            .map_err(|_| "Failed to record vote")?;
+
+        let mut poll_views = PollView::load_for_posts(&mut conn, user_id, &[post])
+            .map_err(|_| "Failed to load poll")?;
+
+        Ok(poll_views.remove(&post_id).ok_or(Refusal::NotFound))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Ok(poll_view))) => HttpResponse::Ok().json(poll_view),
+        Ok(Ok(Err(Refusal::NotFound))) => HttpResponse::NotFound().json(json!({
+            "error": "Poll not found"
+        })),
+        Ok(Ok(Err(Refusal::Invalid(e)))) => HttpResponse::BadRequest().json(json!({
+            "error": e
+        })),
+        Ok(Ok(Err(Refusal::Forbidden(e)))) => HttpResponse::Forbidden().json(json!({
+            "error": e
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
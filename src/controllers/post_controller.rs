@@ -1,70 +1,112 @@
 use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, delete, get, post, put, web};
-use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use chrono::NaiveDateTime;
+use diesel::dsl::{IntervalDsl, now};
+use diesel::pg::PgConnection;
+use diesel::sql_types::{Bool, Timestamp};
+use diesel::{
+    BoolExpressionMethods, Connection, ExpressionMethods, IntoSql, NullableExpressionMethods,
+    OptionalExtension, QueryDsl, RunQueryDsl,
+};
 use serde::Deserialize;
 use serde_json::json;
 use utoipa::ToSchema;
 
 use crate::models::{
-    post::{NewPost, Post},
+    pinned_post,
+    poll::{NewPoll, NewPollOption},
+    post::{NewPost, Post, ReplyAudience, Visibility},
+    post_revision::{NewPostRevision, PostRevision, PostVersion},
+    post_view::PostView,
     user::{AuthedUserId, User},
+    user_settings::UserSettings,
+};
+use crate::schema::{media, poll_options, polls, post_revisions, posts};
+use crate::util::{
+    config,
+    content::{self, ContentError, ContentErrorResponse},
+    db::DbPool,
+    language::{self, LanguageFilter},
+    views::ViewRecorder,
 };
-use crate::schema::{posts, users};
-use crate::util::db::DbPool;
+
+const MAX_MEDIA_PER_POST: usize = 4;
+const MIN_POLL_OPTIONS: usize = 2;
+const MAX_POLL_OPTIONS: usize = 4;
+const MAX_POLL_OPTION_LENGTH: usize = 25;
+const MIN_POLL_DURATION_MINUTES: i32 = 5;
+const MAX_POLL_DURATION_MINUTES: i32 = 7 * 24 * 60;
+const MAX_CONTENT_WARNING_LENGTH: usize = 100;
 
 /// Get all posts
+///
+/// Includes the posts the current user can see, except unlisted posts of other users.
+/// Without `lang`, only posts in the user's `preferred_languages` are included if they
+/// set any, along with posts whose language is unknown.
 #[utoipa::path(
+    params(LanguageFilter),
     security(
         ("bearer_auth" = [])
     ),
     responses(
-        (status = 200, description = "List of all posts", body = Vec<Post>),
+        (status = 200, description = "List of all posts", body = Vec<PostView>),
+        (status = 400, description = "Unknown language code"),
         (status = 401, description = "Unauthorized"),
         (status = 500, description = "Server error")
     )
 )]
 #[get("/posts")]
-pub async fn get_all_posts(pool: web::Data<DbPool>) -> impl Responder {
+pub async fn get_all_posts(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    views: web::Data<ViewRecorder>,
+    filter: web::Query<LanguageFilter>,
+) -> impl Responder {
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let languages = match filter.languages() {
+        Ok(languages) => languages,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(json!({
+                "error": e
+            }));
+        }
+    };
+
     // Use a web::block to offload database operations to a separate thread
     let result = web::block(move || {
         let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
 
-        // Fetch all posts with user information
-        let posts_result = posts::table
+        let mut query = Post::live().filter(Post::is_listed_for(user_id));
+        match languages {
+            Some(languages) => query = query.filter(Post::is_in_languages(languages, false)),
+            None => {
+                // Posts whose language is unknown might be in a preferred language
+                let preferred_languages = UserSettings::load(&mut conn, user_id)
+                    .map_err(|_| "Failed to load settings")?
+                    .preferred_languages;
+                if !preferred_languages.is_empty() {
+                    query = query.filter(Post::is_in_languages(preferred_languages, true));
+                }
+            }
+        }
+
+        // Fetch all posts
+        let posts_result = query
             .load::<Post>(&mut conn)
             .map_err(|_| "Failed to load posts")?;
 
-        // For each post, get the user information
-        let mut posts_with_users = Vec::new();
-        for post in posts_result {
-            let user = users::table
-                .find(post.user_id)
-                .first::<User>(&mut conn)
-                .map_err(|_| "Failed to find user")?;
-
-            posts_with_users.push((post, user));
-        }
+        // Attach user information and engagement counts
+        let post_views = PostView::load_many(&mut conn, user_id, posts_result)
+            .map_err(|_| "Failed to load post details")?;
 
-        Ok::<_, &'static str>(posts_with_users)
+        Ok::<_, &'static str>(post_views)
     })
     .await;
 
     match result {
-        Ok(Ok(posts_with_users)) => {
-            // Convert to JSON response
-            let response_data = posts_with_users
-                .into_iter()
-                .map(|(post, user)| {
-                    json!({
-                        "id": post.id,
-                        "user_id": post.user_id,
-                        "username": user.username,
-                        "content": post.content,
-                        "created_at": post.created_at,
-                    })
-                })
-                .collect::<Vec<_>>();
-
-            HttpResponse::Ok().json(response_data)
+        Ok(Ok(post_views)) => {
+            views.record_impressions(user_id, &post_views);
+            HttpResponse::Ok().json(post_views)
         }
         Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
             "error": e
@@ -81,35 +123,40 @@ pub async fn get_all_posts(pool: web::Data<DbPool>) -> impl Responder {
         ("bearer_auth" = [])
     ),
     responses(
-        (status = 200, description = "The post was found", body = Post),
+        (status = 200, description = "The post was found", body = PostView),
         (status = 401, description = "Unauthorized"),
         (status = 404, description = "Post not found"),
         (status = 500, description = "Server error")
     )
 )]
 #[get("/posts/{id}")]
-pub async fn get_post_by_id(pool: web::Data<DbPool>, id: web::Path<i32>) -> impl Responder {
+pub async fn get_post_by_id(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    views: web::Data<ViewRecorder>,
+    id: web::Path<i32>,
+) -> impl Responder {
     let post_id = id.into_inner();
 
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
     // Use a web::block to offload database operations to a separate thread
     let result = web::block(move || {
         let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
 
-        // Find the post by ID
-        let post = posts::table
-            .find(post_id)
+        // Find the post by ID, among those the user can see
+        let post = Post::visible_to(user_id)
+            .filter(posts::id.eq(post_id))
             .first::<Post>(&mut conn)
             .optional()
             .map_err(|_| "Database error finding post")?;
 
-        // If post is found, get the user information
+        // If post is found, attach user information and engagement counts
         if let Some(post) = post {
-            let user = users::table
-                .find(post.user_id)
-                .first::<User>(&mut conn)
-                .map_err(|_| "Failed to find user")?;
+            let post_view = PostView::load(&mut conn, user_id, post)
+                .map_err(|_| "Failed to load post details")?;
 
-            Ok::<_, &'static str>(Some((post, user)))
+            Ok::<_, &'static str>(Some(post_view))
         } else {
             Ok(None)
         }
@@ -117,13 +164,10 @@ pub async fn get_post_by_id(pool: web::Data<DbPool>, id: web::Path<i32>) -> impl
     .await;
 
     match result {
-        Ok(Ok(Some((post, user)))) => HttpResponse::Ok().json(json!({
-            "id": post.id,
-            "user_id": post.user_id,
-            "username": user.username,
-            "content": post.content,
-            "created_at": post.created_at,
-        })),
+        Ok(Ok(Some(post_view))) => {
+            views.record_detail_view(user_id, &post_view);
+            HttpResponse::Ok().json(post_view)
+        }
         Ok(Ok(None)) => HttpResponse::NotFound().json(json!({
             "error": "Post not found"
         })),
@@ -139,20 +183,148 @@ pub async fn get_post_by_id(pool: web::Data<DbPool>, id: web::Path<i32>) -> impl
 /// Used for API requests when creating a new post
 #[derive(Deserialize, ToSchema)]
 pub struct CreatePostRequest {
-    /// Content of the tweet
+    /// Content of the tweet, at most 280 characters with CJK characters and emoji
+    /// counting double and URLs counting as 23
     pub content: String,
+    /// ID of the post to quote, if any
+    #[serde(default)]
+    pub quoted_post_id: Option<i32>,
+    /// ID of the post to reply to, if any. Ignored when editing a post.
+    #[serde(default)]
+    pub in_reply_to_id: Option<i32>,
+    /// Who can reply to the post, defaulting to everyone. Ignored when editing a post,
+    /// see `PUT /posts/{id}/reply-audience` instead.
+    #[serde(default)]
+    pub reply_audience: Option<ReplyAudience>,
+    /// When to publish the post (UTC), to schedule it instead of publishing it now.
+    /// Ignored when editing a post.
+    #[serde(default)]
+    #[schema(value_type = Option<String>, format = "date-time", example = "2025-04-20T09:00:00")]
+    pub publish_at: Option<NaiveDateTime>,
+    /// Poll to attach to the post, if any. Ignored when editing a post.
+    #[serde(default)]
+    pub poll: Option<PollRequest>,
+    /// IDs of up to 4 uploaded images to attach, in order. Ignored when editing a post.
+    #[serde(default)]
+    pub media_ids: Vec<i32>,
+    /// Who can see the post, defaulting to the author's `default_visibility` setting.
+    /// Ignored when editing a post, see `PUT /posts/{id}/visibility` instead.
+    #[serde(default)]
+    pub visibility: Option<Visibility>,
+    /// Warning shown instead of the content until it is expanded, at most 100
+    /// characters. Ignored when editing a post, see `PUT /posts/{id}/content-warning`
+    /// instead.
+    #[serde(default)]
+    pub content_warning: Option<String>,
+    /// Whether the post is sensitive, such as its media, which is then hidden until it
+    /// is expanded. Ignored when editing a post.
+    #[serde(default)]
+    pub sensitive: bool,
+    /// ISO 639-1 code of the language of the post, detected from its content when
+    /// missing. Ignored when editing a post, see `PUT /posts/{id}/language` instead.
+    #[serde(default)]
+    pub lang: Option<String>,
+}
+
+// Trims a content warning, dropping it when blank
+fn normalize_content_warning(content_warning: Option<&str>) -> Result<Option<String>, String> {
+    let content_warning = content_warning
+        .map(|content_warning| content_warning.trim().to_string())
+        .filter(|content_warning| !content_warning.is_empty());
+    if content_warning
+        .as_ref()
+        .is_some_and(|content_warning| content_warning.chars().count() > MAX_CONTENT_WARNING_LENGTH)
+    {
+        return Err(format!(
+            "Content warning must be at most {} characters",
+            MAX_CONTENT_WARNING_LENGTH
+        ));
+    }
+    Ok(content_warning)
+}
+
+/// Used for API requests when attaching a poll to a new post
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({
+    "options": ["Tabs", "Spaces"],
+    "duration_minutes": 1440
+}))]
+pub struct PollRequest {
+    /// Between 2 and 4 different options, each at most 25 characters
+    pub options: Vec<String>,
+    /// How long the poll is open after the post is published, between 5 minutes and
+    /// 7 days
+    pub duration_minutes: i32,
+}
+
+impl PollRequest {
+    /// Checks the poll, returning its options trimmed
+    fn validate(&self) -> Result<Vec<String>, String> {
+        if !(MIN_POLL_OPTIONS..=MAX_POLL_OPTIONS).contains(&self.options.len()) {
+            return Err(format!(
+                "A poll must have between {} and {} options",
+                MIN_POLL_OPTIONS, MAX_POLL_OPTIONS
+            ));
+        }
+
+        let options: Vec<String> = self
+            .options
+            .iter()
+            .map(|option| option.trim().to_string())
+            .collect();
+        if options
+            .iter()
+            .any(|option| option.is_empty() || option.chars().count() > MAX_POLL_OPTION_LENGTH)
+        {
+            return Err(format!(
+                "Poll options must be between 1 and {} characters",
+                MAX_POLL_OPTION_LENGTH
+            ));
+        }
+
+        let mut distinct: Vec<String> =
+            options.iter().map(|option| option.to_lowercase()).collect();
+        distinct.sort();
+        distinct.dedup();
+        if distinct.len() < options.len() {
+            return Err("Poll options must be different".to_string());
+        }
+
+        if !(MIN_POLL_DURATION_MINUTES..=MAX_POLL_DURATION_MINUTES).contains(&self.duration_minutes)
+        {
+            return Err(format!(
+                "Poll duration must be between {} minutes and {} days",
+                MIN_POLL_DURATION_MINUTES,
+                MAX_POLL_DURATION_MINUTES / (24 * 60)
+            ));
+        }
+
+        Ok(options)
+    }
 }
 
 /// Create a new post
+///
+/// With `publish_at`, the post is scheduled: it stays hidden from everyone else until
+/// it is published at that time.
+///
+/// Retrying with the same `Idempotency-Key` replays the first response instead of
+/// creating the post again, which works for every mutating endpoint.
 #[utoipa::path(
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Unique key making retries safe, kept for `IDEMPOTENCY_KEY_TTL_HOURS`. Reusing it for a different request is refused with a 422.")
+    ),
     request_body = CreatePostRequest,
     security(
         ("bearer_auth" = [])
     ),
     responses(
-        (status = 201, description = "Post created successfully", body = Post),
+        (status = 201, description = "Post created successfully", body = PostView),
         (status = 400, description = "Invalid input"),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not allowed to reply to the post"),
+        (status = 409, description = "A request with the same Idempotency-Key is still being handled"),
+        (status = 422, description = "Invalid post content, or Idempotency-Key reused for a different request", body = ContentErrorResponse),
         (status = 500, description = "Server error")
     )
 )]
@@ -166,45 +338,277 @@ pub async fn create_post(
 
     // Use a web::block to offload database operations to a separate thread
     let result = web::block(move || {
-        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+        let mut conn = pool
+            .get()
+            .map_err(|_| CreatePostError::Failed("Failed to get DB connection"))?;
 
-        // Create new post
-        let new_post = NewPost {
-            user_id: user_id,
-            content: post_req.content.clone(),
-        };
-
-        // Insert post into database
-        let post = diesel::insert_into(posts::table)
-            .values(&new_post)
-            .get_result::<Post>(&mut conn)
-            .map_err(|_| "Failed to create post")?;
+        let post = insert_post(&mut conn, user_id, &post_req)?;
 
-        Ok::<_, &'static str>(post)
+        PostView::load(&mut conn, user_id, post)
+            .map_err(|_| CreatePostError::Failed("Failed to load post details"))
     })
     .await;
 
     match result {
-        Ok(Ok(post)) => HttpResponse::Created().json(post),
-        Ok(Err(e)) => HttpResponse::BadRequest().json(json!({
-            "error": e
-        })),
+        Ok(Ok(post_view)) => HttpResponse::Created().json(post_view),
+        Ok(Err(e)) => e.into_response(),
         Err(e) => HttpResponse::InternalServerError().json(json!({
             "error": e.to_string()
         })),
     }
 }
 
+/// Reasons a post cannot be created
+pub enum CreatePostError {
+    /// The content of the post is invalid
+    InvalidContent(Vec<ContentError>),
+    /// Something else in the request is invalid
+    Invalid(String),
+    /// The user is not allowed to do what the request asks
+    Forbidden(&'static str),
+    /// The post could not be stored
+    Failed(&'static str),
+}
+
+impl CreatePostError {
+    /// Builds the response explaining why the post was not created
+    pub fn into_response(self) -> HttpResponse {
+        match self {
+            CreatePostError::InvalidContent(errors) => {
+                HttpResponse::UnprocessableEntity().json(ContentErrorResponse::new(errors))
+            }
+            CreatePostError::Invalid(e) => HttpResponse::BadRequest().json(json!({
+                "error": e
+            })),
+            CreatePostError::Forbidden(e) => HttpResponse::Forbidden().json(json!({
+                "error": e
+            })),
+            CreatePostError::Failed(e) => HttpResponse::InternalServerError().json(json!({
+                "error": e
+            })),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for CreatePostError {
+    fn from(_: diesel::result::Error) -> Self {
+        CreatePostError::Failed("Database error")
+    }
+}
+
+/// Validates a new post and stores it along with its hashtags and mentions
+///
+/// Everything that creates posts goes through here so the same rules apply everywhere.
+pub fn insert_post(
+    conn: &mut PgConnection,
+    user_id: i32,
+    post_req: &CreatePostRequest,
+) -> Result<Post, CreatePostError> {
+    let content = content::validate(&post_req.content).map_err(CreatePostError::InvalidContent)?;
+    let content_warning = normalize_content_warning(post_req.content_warning.as_deref())
+        .map_err(CreatePostError::Invalid)?;
+    let lang = match &post_req.lang {
+        Some(lang) => {
+            Some(language::normalize(lang).map_err(|e| CreatePostError::Invalid(e.to_string()))?)
+        }
+        None => language::detect(&content),
+    };
+
+    let mut distinct_media_ids = post_req.media_ids.clone();
+    distinct_media_ids.sort_unstable();
+    distinct_media_ids.dedup();
+    if distinct_media_ids.len() < post_req.media_ids.len() {
+        return Err(CreatePostError::Invalid(
+            "Media can only be attached once".to_string(),
+        ));
+    }
+    if post_req.media_ids.len() > MAX_MEDIA_PER_POST {
+        return Err(CreatePostError::Invalid(format!(
+            "A post can have at most {} media attachments",
+            MAX_MEDIA_PER_POST
+        )));
+    }
+
+    let settings = UserSettings::load(conn, user_id)
+        .map_err(|_| CreatePostError::Failed("Database error loading settings"))?;
+
+    // Users can require themselves to describe every image they post
+    if settings.require_alt_text && !post_req.media_ids.is_empty() {
+        let undescribed = media::table
+            .filter(media::id.eq_any(&post_req.media_ids))
+            .filter(media::user_id.eq(user_id))
+            .filter(media::alt_text.is_null())
+            .count()
+            .get_result::<i64>(conn)
+            .map_err(|_| CreatePostError::Failed("Database error checking alt text"))?;
+
+        if undescribed > 0 {
+            return Err(CreatePostError::Invalid(
+                "Alt text is required for all media".to_string(),
+            ));
+        }
+    }
+
+    let poll_options = match &post_req.poll {
+        Some(poll) => Some(poll.validate().map_err(CreatePostError::Invalid)?),
+        None => None,
+    };
+
+    // Scheduled posts must be due in the future, by the database clock
+    if let Some(publish_at) = post_req.publish_at {
+        let is_future = diesel::select(publish_at.into_sql::<Timestamp>().gt(now))
+            .get_result::<bool>(conn)
+            .map_err(|_| CreatePostError::Failed("Database error checking publish time"))?;
+
+        if !is_future {
+            return Err(CreatePostError::Invalid(
+                "Publish time must be in the future".to_string(),
+            ));
+        }
+    }
+
+    // Make sure the quoted post exists
+    if let Some(quoted_post_id) = post_req.quoted_post_id {
+        let quoted_exists = Post::visible_to(user_id)
+            .filter(posts::id.eq(quoted_post_id))
+            .first::<Post>(conn)
+            .optional()
+            .map_err(|_| CreatePostError::Failed("Database error finding quoted post"))?;
+
+        if quoted_exists.is_none() {
+            return Err(CreatePostError::Invalid(
+                "Quoted post not found".to_string(),
+            ));
+        }
+    }
+
+    // Replies join the conversation of the post they reply to, if its author lets
+    // the user reply
+    let conversation_id = match post_req.in_reply_to_id {
+        Some(in_reply_to_id) => {
+            let parent = Post::visible_to(user_id)
+                .filter(posts::id.eq(in_reply_to_id))
+                .select((posts::all_columns, Post::accepts_replies_from(user_id)))
+                .first::<(Post, bool)>(conn)
+                .optional()
+                .map_err(|_| CreatePostError::Failed("Database error finding replied post"))?;
+
+            match parent {
+                Some((parent, true)) => Some(parent.conversation_id.unwrap_or(parent.id)),
+                Some((_, false)) => {
+                    return Err(CreatePostError::Forbidden("You cannot reply to this post"));
+                }
+                None => {
+                    return Err(CreatePostError::Invalid(
+                        "Replied post not found".to_string(),
+                    ));
+                }
+            }
+        }
+        None => None,
+    };
+
+    // Create new post
+    let new_post = NewPost {
+        user_id,
+        content,
+        quoted_post_id: post_req.quoted_post_id,
+        publish_at: post_req.publish_at,
+        visibility: post_req.visibility.unwrap_or(settings.default_visibility),
+        in_reply_to_id: post_req.in_reply_to_id,
+        conversation_id,
+        reply_audience: post_req.reply_audience.unwrap_or(ReplyAudience::Everyone),
+        content_warning,
+        sensitive: post_req.sensitive,
+        lang,
+        lang_overridden: post_req.lang.is_some(),
+    };
+
+    // Insert post into database along with its hashtags, mentions, poll and media
+    conn.transaction::<_, CreatePostError, _>(|conn| {
+        let mut post = diesel::insert_into(posts::table)
+            .values(&new_post)
+            .get_result::<Post>(conn)?;
+        // Posts that are not replies start their own conversation
+        if post.conversation_id.is_none() {
+            post = diesel::update(&post)
+                .set(posts::conversation_id.eq(post.id))
+                .get_result::<Post>(conn)?;
+        }
+        post.index_entities(conn)?;
+
+        if let (Some(poll), Some(options)) = (&post_req.poll, poll_options) {
+            diesel::insert_into(polls::table)
+                .values(&NewPoll {
+                    post_id: post.id,
+                    duration_minutes: poll.duration_minutes,
+                })
+                .execute(conn)?;
+
+            let options = options
+                .into_iter()
+                .enumerate()
+                .map(|(position, label)| NewPollOption {
+                    post_id: post.id,
+                    position: position as i32,
+                    label,
+                })
+                .collect::<Vec<_>>();
+            diesel::insert_into(poll_options::table)
+                .values(&options)
+                .execute(conn)?;
+        }
+
+        // Only the author's own media that is not attached yet can be attached
+        for (position, media_id) in post_req.media_ids.iter().enumerate() {
+            let attached = diesel::update(
+                media::table
+                    .filter(media::id.eq(media_id))
+                    .filter(media::user_id.eq(user_id))
+                    .filter(media::post_id.is_null()),
+            )
+            .set((
+                media::post_id.eq(post.id),
+                media::position.eq(position as i32),
+            ))
+            .execute(conn)?;
+
+            if attached == 0 {
+                return Err(CreatePostError::Invalid("Media not found".to_string()));
+            }
+        }
+
+        Ok(post)
+    })
+    .map_err(|e| match e {
+        CreatePostError::Invalid(e) => CreatePostError::Invalid(e),
+        _ => CreatePostError::Failed("Failed to create post"),
+    })
+}
+
+/// Reasons for refusing a change to a post
+enum Refusal {
+    /// The post does not exist or belongs to someone else
+    NotFound,
+    /// The change is not allowed anymore
+    Forbidden(&'static str),
+}
+
 /// Update an existing post
+///
+/// Posts can only be edited within `POST_EDIT_WINDOW_MINUTES` of their creation and
+/// at most `POST_MAX_EDITS` times. Each edit keeps the previous version in the history.
 #[utoipa::path(
     request_body = CreatePostRequest,
     security(
         ("bearer_auth" = [])
     ),
     responses(
-        (status = 200, description = "Post updated successfully", body = Post),
+        (status = 200, description = "Post updated successfully", body = PostView),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Post can no longer be edited"),
         (status = 404, description = "Post not found"),
+        (status = 422, description = "Invalid post content", body = ContentErrorResponse),
         (status = 500, description = "Server error")
     )
 )]
@@ -219,34 +623,538 @@ pub async fn update_post(
 
     let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
 
+    let content = match content::validate(&post_req.content) {
+        Ok(content) => content,
+        Err(errors) => {
+            return HttpResponse::UnprocessableEntity().json(ContentErrorResponse::new(errors));
+        }
+    };
+
+    let edit_window_minutes = config::env_or("POST_EDIT_WINDOW_MINUTES", 60);
+    let max_edits = config::env_or("POST_MAX_EDITS", 5);
+
     // Use a web::block to offload database operations to a separate thread
     let result = web::block(move || {
         let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
 
-        let post = posts::table
-            .find(post_id)
+        // Lock the post so that concurrent edits cannot exceed the limits, then keep the
+        // superseded version, update the post and reindex it together
+        let updated_post = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                // Compare against the database clock, which also set created_at
+                let post = posts::table
+                    .filter(Post::is_live())
+                    .filter(posts::id.eq(post_id))
+                    .filter(posts::user_id.eq(user_id))
+                    .select((
+                        posts::all_columns,
+                        posts::created_at.gt(now - edit_window_minutes.minutes()),
+                    ))
+                    .for_update()
+                    .first::<(Post, bool)>(conn)
+                    .optional()?;
+
+                let Some((post, within_window)) = post else {
+                    return Ok(Err(Refusal::NotFound));
+                };
+
+                // Saving the same content again is not an edit
+                if post.content == content {
+                    return Ok(Ok(post));
+                }
+
+                if !within_window {
+                    return Ok(Err(Refusal::Forbidden("Edit window has expired")));
+                }
+                if post.edit_count >= max_edits {
+                    return Ok(Err(Refusal::Forbidden("Maximum number of edits reached")));
+                }
+
+                diesel::insert_into(post_revisions::table)
+                    .values(&NewPostRevision {
+                        post_id: post.id,
+                        content: post.content.clone(),
+                        created_at: post.edited_at.unwrap_or(post.created_at),
+                    })
+                    .execute(conn)?;
+
+                // The language follows the content, unless the author chose it
+                let lang = match post.lang_overridden {
+                    true => post.lang.clone(),
+                    false => language::detect(&content),
+                };
+
+                let updated_post = diesel::update(&post)
+                    .set((
+                        posts::content.eq(&content),
+                        posts::edited_at.eq(now),
+                        posts::edit_count.eq(posts::edit_count + 1),
+                        posts::lang.eq(lang),
+                    ))
+                    .get_result::<Post>(conn)?;
+                updated_post.index_entities(conn)?;
+                Ok(Ok(updated_post))
+            })
+            .map_err(|_| "Failed to update post")?;
+
+        let updated_post = match updated_post {
+            Ok(updated_post) => updated_post,
+            Err(refusal) => return Ok::<_, &'static str>(Err(refusal)),
+        };
+
+        let post_view = PostView::load(&mut conn, user_id, updated_post)
+            .map_err(|_| "Failed to load post details")?;
+
+        Ok(Ok(post_view))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Ok(post_view))) => HttpResponse::Ok().json(post_view),
+        Ok(Ok(Err(Refusal::NotFound))) => HttpResponse::NotFound().json(json!({
+            "error": "Post not found"
+        })),
+        Ok(Ok(Err(Refusal::Forbidden(e)))) => HttpResponse::Forbidden().json(json!({
+            "error": e
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Used for API requests when changing who can see a post
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({
+    "visibility": "followers"
+}))]
+pub struct UpdateVisibilityRequest {
+    /// Who can see the post from now on
+    pub visibility: Visibility,
+}
+
+/// Change who can see a post
+///
+/// Unlike editing its content, this is allowed at any time. Posts that stop being
+/// public are unpinned.
+#[utoipa::path(
+    request_body = UpdateVisibilityRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Visibility updated successfully", body = PostView),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Post not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[put("/posts/{id}/visibility")]
+pub async fn update_post_visibility(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    visibility_req: web::Json<UpdateVisibilityRequest>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let visibility = visibility_req.into_inner().visibility;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        // Scheduled posts can change audience before they are published too
+        let updated_post = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                let updated_post = diesel::update(
+                    posts::table
+                        .filter(posts::id.eq(post_id))
+                        .filter(posts::user_id.eq(user_id))
+                        .filter(posts::deleted_at.is_null()),
+                )
+                .set(posts::visibility.eq(visibility))
+                .get_result::<Post>(conn)
+                .optional()?;
+
+                if updated_post.is_some() && visibility != Visibility::Public {
+                    pinned_post::unpin(conn, post_id)?;
+                }
+                Ok(updated_post)
+            })
+            .map_err(|_| "Failed to update post")?;
+
+        let Some(updated_post) = updated_post else {
+            return Ok::<_, &'static str>(None);
+        };
+
+        let post_view = PostView::load(&mut conn, user_id, updated_post)
+            .map_err(|_| "Failed to load post details")?;
+
+        Ok(Some(post_view))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Some(post_view))) => HttpResponse::Ok().json(post_view),
+        Ok(Ok(None)) => HttpResponse::NotFound().json(json!({
+            "error": "Post not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Used for API requests when changing who can reply to a post
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({
+    "reply_audience": "following"
+}))]
+pub struct UpdateReplyAudienceRequest {
+    /// Who can reply to the post from now on
+    pub reply_audience: ReplyAudience,
+}
+
+/// Change who can reply to a post
+///
+/// Like visibility, this can be changed at any time. Existing replies are kept.
+#[utoipa::path(
+    request_body = UpdateReplyAudienceRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Reply audience updated successfully", body = PostView),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Post not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[put("/posts/{id}/reply-audience")]
+pub async fn update_reply_audience(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    audience_req: web::Json<UpdateReplyAudienceRequest>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let reply_audience = audience_req.into_inner().reply_audience;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let updated_post = diesel::update(
+            posts::table
+                .filter(posts::id.eq(post_id))
+                .filter(posts::user_id.eq(user_id))
+                .filter(posts::deleted_at.is_null()),
+        )
+        .set(posts::reply_audience.eq(reply_audience))
+        .get_result::<Post>(&mut conn)
+        .optional()
+        .map_err(|_| "Failed to update post")?;
+
+        let Some(updated_post) = updated_post else {
+            return Ok::<_, &'static str>(None);
+        };
+
+        let post_view = PostView::load(&mut conn, user_id, updated_post)
+            .map_err(|_| "Failed to load post details")?;
+
+        Ok(Some(post_view))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Some(post_view))) => HttpResponse::Ok().json(post_view),
+        Ok(Ok(None)) => HttpResponse::NotFound().json(json!({
+            "error": "Post not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Used for API requests when changing the content warning of a post
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({
+    "content_warning": "Spoilers for the season finale",
+    "sensitive": false
+}))]
+pub struct UpdateContentWarningRequest {
+    /// Warning shown instead of the content until it is expanded, at most 100
+    /// characters, or null to remove it
+    #[serde(default)]
+    pub content_warning: Option<String>,
+    /// Whether the post is sensitive. Posts a moderator marked as sensitive stay
+    /// sensitive.
+    #[serde(default)]
+    pub sensitive: bool,
+}
+
+/// Change the content warning of a post
+///
+/// Like visibility, this can be changed at any time.
+#[utoipa::path(
+    request_body = UpdateContentWarningRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Content warning updated successfully", body = PostView),
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Post not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[put("/posts/{id}/content-warning")]
+pub async fn update_content_warning(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    warning_req: web::Json<UpdateContentWarningRequest>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let content_warning = match normalize_content_warning(warning_req.content_warning.as_deref()) {
+        Ok(content_warning) => content_warning,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(json!({
+                "error": e
+            }));
+        }
+    };
+    let sensitive = warning_req.sensitive;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let updated_post = diesel::update(
+            posts::table
+                .filter(posts::id.eq(post_id))
+                .filter(posts::user_id.eq(user_id))
+                .filter(posts::deleted_at.is_null()),
+        )
+        .set((
+            posts::content_warning.eq(content_warning),
+            posts::sensitive.eq(sensitive),
+        ))
+        .get_result::<Post>(&mut conn)
+        .optional()
+        .map_err(|_| "Failed to update post")?;
+
+        let Some(updated_post) = updated_post else {
+            return Ok::<_, &'static str>(None);
+        };
+
+        let post_view = PostView::load(&mut conn, user_id, updated_post)
+            .map_err(|_| "Failed to load post details")?;
+
+        Ok(Some(post_view))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Some(post_view))) => HttpResponse::Ok().json(post_view),
+        Ok(Ok(None)) => HttpResponse::NotFound().json(json!({
+            "error": "Post not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Used for API requests when changing the language of a post
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({
+    "lang": "pt"
+}))]
+pub struct UpdateLanguageRequest {
+    /// ISO 639-1 code of the language of the post, or null to detect it from the
+    /// content again
+    #[serde(default)]
+    pub lang: Option<String>,
+}
+
+/// Change the language of a post
+///
+/// Authors can correct the detected language at any time. Languages chosen this way
+/// are kept when the post is edited.
+#[utoipa::path(
+    request_body = UpdateLanguageRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Language updated successfully", body = PostView),
+        (status = 400, description = "Unknown language code"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Post not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[put("/posts/{id}/language")]
+pub async fn update_post_language(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    language_req: web::Json<UpdateLanguageRequest>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let lang = match language_req
+        .lang
+        .as_deref()
+        .map(language::normalize)
+        .transpose()
+    {
+        Ok(lang) => lang,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(json!({
+                "error": e
+            }));
+        }
+    };
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let post = Post::live()
+            .filter(posts::id.eq(post_id))
             .filter(posts::user_id.eq(user_id))
             .first::<Post>(&mut conn)
             .optional()
             .map_err(|_| "Database error finding post")?;
 
-        if post.is_none() {
+        let Some(post) = post else {
             return Ok::<_, &'static str>(None);
-        }
-        let post = post.unwrap();
+        };
+
+        let lang_overridden = lang.is_some();
+        let lang = lang.or_else(|| language::detect(&post.content));
 
-        // Update post
         let updated_post = diesel::update(&post)
-            .set(posts::content.eq(&post_req.content))
+            .set((
+                posts::lang.eq(lang),
+                posts::lang_overridden.eq(lang_overridden),
+            ))
             .get_result::<Post>(&mut conn)
             .map_err(|_| "Failed to update post")?;
 
-        Ok::<Option<Post>, &'static str>(Some(updated_post))
+        let post_view = PostView::load(&mut conn, user_id, updated_post)
+            .map_err(|_| "Failed to load post details")?;
+
+        Ok(Some(post_view))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Some(post_view))) => HttpResponse::Ok().json(post_view),
+        Ok(Ok(None)) => HttpResponse::NotFound().json(json!({
+            "error": "Post not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Get the edit history of a post
+#[utoipa::path(
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "All versions of the post, oldest first", body = Vec<PostVersion>),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Post not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[get("/posts/{id}/history")]
+pub async fn get_post_history(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let post = Post::visible_to(user_id)
+            .filter(posts::id.eq(post_id))
+            .first::<Post>(&mut conn)
+            .optional()
+            .map_err(|_| "Database error finding post")?;
+
+        let Some(post) = post else {
+            return Ok::<_, &'static str>(None);
+        };
+
+        let revisions = post_revisions::table
+            .filter(post_revisions::post_id.eq(post.id))
+            .order(post_revisions::id.asc())
+            .load::<PostRevision>(&mut conn)
+            .map_err(|_| "Failed to load post history")?;
+
+        // Superseded versions followed by the current one
+        let mut versions = revisions
+            .into_iter()
+            .map(|revision| (revision.content, revision.created_at))
+            .collect::<Vec<_>>();
+        versions.push((post.content, post.edited_at.unwrap_or(post.created_at)));
+
+        let versions = versions
+            .into_iter()
+            .enumerate()
+            .map(|(version, (content, created_at))| PostVersion {
+                version: version as i32,
+                content,
+                created_at,
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Some(versions))
     })
     .await;
 
     match result {
-        Ok(Ok(Some(post))) => HttpResponse::Ok().json(post),
+        Ok(Ok(Some(versions))) => HttpResponse::Ok().json(versions),
         Ok(Ok(None)) => HttpResponse::NotFound().json(json!({
             "error": "Post not found"
         })),
@@ -260,6 +1168,10 @@ pub async fn update_post(
 }
 
 /// Delete a post
+///
+/// Posts are only marked as deleted: the author can undo the deletion for
+/// `POST_DELETE_UNDO_SECONDS` and moderators can restore it until it is purged
+/// `POST_RETENTION_DAYS` later. Deleted posts are unpinned.
 #[utoipa::path(
     security(
         ("bearer_auth" = [])
@@ -272,30 +1184,47 @@ pub async fn update_post(
     )
 )]
 #[delete("/posts/{id}")]
-pub async fn delete_post(pool: web::Data<DbPool>, id: web::Path<i32>) -> impl Responder {
+pub async fn delete_post(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> impl Responder {
     let post_id = id.into_inner();
 
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
     // Use a web::block to offload database operations to a separate thread
     let result = web::block(move || {
         let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
 
-        // Check if post exists
-        let post_exists = posts::table
-            .find(post_id)
-            .first::<Post>(&mut conn)
-            .optional()
-            .map_err(|_| "Database error checking post")?;
+        let is_moderator = User::moderator_status(&mut conn, user_id)
+            .map_err(|_| "Database error checking user")?;
 
-        if post_exists.is_none() {
-            return Ok::<_, &'static str>(false);
-        }
+        let deleted = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                // Authors can delete their own posts, moderators anyone's
+                let deleted = diesel::update(
+                    posts::table
+                        .filter(posts::id.eq(post_id))
+                        .filter(posts::deleted_at.is_null())
+                        .filter(
+                            posts::user_id
+                                .eq(user_id)
+                                .or(is_moderator.into_sql::<Bool>()),
+                        ),
+                )
+                .set((posts::deleted_at.eq(now), posts::deleted_by.eq(user_id)))
+                .execute(conn)?;
 
-        // Delete post
-        let deleted = diesel::delete(posts::table.find(post_id))
-            .execute(&mut conn)
+                // Restoring the post does not pin it again
+                if deleted > 0 {
+                    pinned_post::unpin(conn, post_id)?;
+                }
+                Ok(deleted)
+            })
             .map_err(|_| "Failed to delete post")?;
 
-        Ok(deleted > 0)
+        Ok::<_, &'static str>(deleted > 0)
     })
     .await;
 
@@ -312,3 +1241,98 @@ pub async fn delete_post(pool: web::Data<DbPool>, id: web::Path<i32>) -> impl Re
         })),
     }
 }
+
+/// Restore a deleted post
+///
+/// Authors can undo their own deletion within `POST_DELETE_UNDO_SECONDS`.
+/// Moderators can restore any deleted post that has not been purged yet.
+#[utoipa::path(
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Post restored successfully", body = PostView),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Undo window has expired"),
+        (status = 404, description = "Deleted post not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[post("/posts/{id}/restore")]
+pub async fn restore_post(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let undo_seconds = config::env_or("POST_DELETE_UNDO_SECONDS", 30);
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let is_moderator = User::moderator_status(&mut conn, user_id)
+            .map_err(|_| "Database error checking user")?;
+
+        // Compare against the database clock, which also set deleted_at
+        let post = posts::table
+            .find(post_id)
+            .filter(posts::deleted_at.is_not_null())
+            .select((
+                posts::all_columns,
+                posts::deleted_at
+                    .assume_not_null()
+                    .gt(now - undo_seconds.seconds()),
+            ))
+            .first::<(Post, bool)>(&mut conn)
+            .optional()
+            .map_err(|_| "Database error finding post")?;
+
+        let Some((post, within_undo_window)) = post else {
+            return Ok::<_, &'static str>(Err(Refusal::NotFound));
+        };
+
+        if !is_moderator {
+            // Authors only get to undo their own deletions
+            if post.user_id != user_id || post.deleted_by != Some(user_id) {
+                return Ok(Err(Refusal::NotFound));
+            }
+            if !within_undo_window {
+                return Ok(Err(Refusal::Forbidden("Undo window has expired")));
+            }
+        }
+
+        let restored_post = diesel::update(&post)
+            .set((
+                posts::deleted_at.eq(None::<NaiveDateTime>),
+                posts::deleted_by.eq(None::<i32>),
+            ))
+            .get_result::<Post>(&mut conn)
+            .map_err(|_| "Failed to restore post")?;
+
+        let post_view = PostView::load(&mut conn, user_id, restored_post)
+            .map_err(|_| "Failed to load post details")?;
+
+        Ok(Ok(post_view))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Ok(post_view))) => HttpResponse::Ok().json(post_view),
+        Ok(Ok(Err(Refusal::NotFound))) => HttpResponse::NotFound().json(json!({
+            "error": "Deleted post not found"
+        })),
+        Ok(Ok(Err(Refusal::Forbidden(e)))) => HttpResponse::Forbidden().json(json!({
+            "error": e
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
@@ -0,0 +1,146 @@
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, delete, post, web};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use serde_json::json;
+
+use crate::models::{
+    post::{Post, Visibility},
+    repost::NewRepost,
+    user::AuthedUserId,
+};
+use crate::schema::{posts, reposts};
+use crate::util::db::DbPool;
+
+/// Reasons for refusing a repost
+enum Refusal {
+    /// The post does not exist or cannot be seen
+    NotFound,
+    /// The post cannot be reposted
+    Forbidden(&'static str),
+}
+
+/// Repost a post
+///
+/// Followers-only and mentioned-only posts can only be reposted by their author, as
+/// reposts are visible to everyone.
+#[utoipa::path(
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 204, description = "Post reposted successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Post cannot be reposted"),
+        (status = 404, description = "Post not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[post("/posts/{id}/repost")]
+pub async fn repost_post(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        // Check if post exists
+        let post = Post::visible_to(user_id)
+            .filter(posts::id.eq(post_id))
+            .first::<Post>(&mut conn)
+            .optional()
+            .map_err(|_| "Database error checking post")?;
+
+        let Some(post) = post else {
+            return Ok::<_, &'static str>(Err(Refusal::NotFound));
+        };
+        let is_shared = matches!(post.visibility, Visibility::Public | Visibility::Unlisted);
+        if !is_shared && post.user_id != user_id {
+            return Ok(Err(Refusal::Forbidden(
+                "Only public and unlisted posts can be reposted",
+            )));
+        }
+
+        // A user can repost a post only once, so reposting again is a no-op
+        diesel::insert_into(reposts::table)
+            .values(&NewRepost { user_id, post_id })
+            .on_conflict_do_nothing()
+            .execute(&mut conn)
+            .map_err(|_| "Failed to repost post")?;
+
+        Ok(Ok(()))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Ok(()))) => HttpResponse::NoContent().finish(),
+        Ok(Ok(Err(Refusal::NotFound))) => HttpResponse::NotFound().json(json!({
+            "error": "Post not found"
+        })),
+        Ok(Ok(Err(Refusal::Forbidden(e)))) => HttpResponse::Forbidden().json(json!({
+            "error": e
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Undo a repost
+#[utoipa::path(
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 204, description = "Repost removed successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Repost not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[delete("/posts/{id}/repost")]
+pub async fn undo_repost(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let deleted = diesel::delete(
+            reposts::table
+                .filter(reposts::user_id.eq(user_id))
+                .filter(reposts::post_id.eq(post_id)),
+        )
+        .execute(&mut conn)
+        .map_err(|_| "Failed to remove repost")?;
+
+        Ok::<_, &'static str>(deleted > 0)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(true)) => HttpResponse::NoContent().finish(),
+        Ok(Ok(false)) => HttpResponse::NotFound().json(json!({
+            "error": "Repost not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
@@ -0,0 +1,230 @@
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, delete, get, put, web};
+use chrono::NaiveDateTime;
+use diesel::dsl::now;
+use diesel::sql_types::Timestamp;
+use diesel::{Connection, ExpressionMethods, IntoSql, OptionalExtension, QueryDsl, RunQueryDsl};
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::models::{media::Media, post::Post, post_view::PostView, user::AuthedUserId};
+use crate::schema::posts;
+use crate::storage::{self, MediaStore};
+use crate::util::{db::DbPool, pagination::Pagination};
+
+/// Get the current user's scheduled posts
+#[utoipa::path(
+    params(Pagination),
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Scheduled posts, soonest first", body = Vec<PostView>),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[get("/scheduled-posts")]
+pub async fn get_scheduled_posts(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    page: web::Query<Pagination>,
+) -> impl Responder {
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let scheduled_posts = posts::table
+            .filter(posts::user_id.eq(user_id))
+            .filter(posts::publish_at.is_not_null())
+            .filter(posts::deleted_at.is_null())
+            .order((posts::publish_at.asc(), posts::id.asc()))
+            .limit(page.limit())
+            .offset(page.offset())
+            .load::<Post>(&mut conn)
+            .map_err(|_| "Failed to load scheduled posts")?;
+
+        let post_views = PostView::load_many(&mut conn, user_id, scheduled_posts)
+            .map_err(|_| "Failed to load post details")?;
+
+        Ok::<_, &'static str>(post_views)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(post_views)) => HttpResponse::Ok().json(post_views),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Used for API requests when rescheduling a post
+#[derive(Deserialize, ToSchema)]
+pub struct ReschedulePostRequest {
+    /// New time to publish the post (UTC)
+    #[schema(value_type = String, format = "date-time", example = "2025-04-20T09:00:00")]
+    pub publish_at: NaiveDateTime,
+}
+
+/// Reschedule a scheduled post
+#[utoipa::path(
+    request_body = ReschedulePostRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Post rescheduled successfully", body = PostView),
+        (status = 400, description = "Publish time is not in the future"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Scheduled post not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[put("/scheduled-posts/{id}")]
+pub async fn reschedule_post(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    reschedule_req: web::Json<ReschedulePostRequest>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+    let publish_at = reschedule_req.publish_at;
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let is_future = diesel::select(publish_at.into_sql::<Timestamp>().gt(now))
+            .get_result::<bool>(&mut conn)
+            .map_err(|_| "Database error checking publish time")?;
+
+        if !is_future {
+            return Ok(Err("Publish time must be in the future"));
+        }
+
+        // Posts the publisher has already released are not scheduled anymore
+        let post = diesel::update(
+            posts::table
+                .filter(posts::id.eq(post_id))
+                .filter(posts::user_id.eq(user_id))
+                .filter(posts::publish_at.is_not_null())
+                .filter(posts::deleted_at.is_null()),
+        )
+        .set(posts::publish_at.eq(publish_at))
+        .get_result::<Post>(&mut conn)
+        .optional()
+        .map_err(|_| "Failed to reschedule post")?;
+
+        let post_view = match post {
+            Some(post) => Some(
+                PostView::load(&mut conn, user_id, post)
+                    .map_err(|_| "Failed to load post details")?,
+            ),
+            None => None,
+        };
+
+        Ok::<_, &'static str>(Ok(post_view))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Ok(Some(post_view)))) => HttpResponse::Ok().json(post_view),
+        Ok(Ok(Ok(None))) => HttpResponse::NotFound().json(json!({
+            "error": "Scheduled post not found"
+        })),
+        Ok(Ok(Err(e))) => HttpResponse::BadRequest().json(json!({
+            "error": e
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Cancel a scheduled post
+///
+/// The post is removed for good, since nobody else has seen it, along with its media.
+#[utoipa::path(
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 204, description = "Scheduled post cancelled successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Scheduled post not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[delete("/scheduled-posts/{id}")]
+pub async fn cancel_scheduled_post(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    store: web::Data<dyn MediaStore>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    let post_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        // Hashtag and mention links and media rows go with the post
+        let media_keys = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                let scheduled = posts::table
+                    .filter(posts::id.eq(post_id))
+                    .filter(posts::user_id.eq(user_id))
+                    .filter(posts::publish_at.is_not_null())
+                    .filter(posts::deleted_at.is_null())
+                    .select(posts::id)
+                    .for_update()
+                    .first::<i32>(conn)
+                    .optional()?;
+
+                let Some(post_id) = scheduled else {
+                    return Ok(None);
+                };
+                let media_keys = Media::storage_keys_for_posts(conn, &[post_id])?;
+
+                diesel::delete(posts::table.find(post_id)).execute(conn)?;
+                Ok(Some(media_keys))
+            })
+            .map_err(|_| "Failed to cancel scheduled post")?;
+
+        let Some(media_keys) = media_keys else {
+            return Ok::<_, &'static str>(false);
+        };
+
+        // Files are only removed once their rows are gone for good
+        storage::delete_all(store.get_ref(), &media_keys);
+
+        Ok(true)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(true)) => HttpResponse::NoContent().finish(),
+        Ok(Ok(false)) => HttpResponse::NotFound().json(json!({
+            "error": "Scheduled post not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
@@ -0,0 +1,148 @@
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, get, patch, web};
+use diesel::{Connection, RunQueryDsl};
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::models::{post::Visibility, user::AuthedUserId, user_settings::UserSettings};
+use crate::schema::user_settings;
+use crate::util::{db::DbPool, language};
+
+/// Used for API requests when changing settings
+///
+/// Only the fields present are changed.
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({
+    "require_alt_text": true,
+    "default_visibility": "followers",
+    "expand_content_warnings": true,
+    "preferred_languages": ["en", "fr"]
+}))]
+pub struct UpdateSettingsRequest {
+    /// Whether images must have alt text before they can be posted
+    #[serde(default)]
+    pub require_alt_text: Option<bool>,
+    /// Visibility of new posts that do not give one
+    #[serde(default)]
+    pub default_visibility: Option<Visibility>,
+    /// Whether posts with a content warning or marked as sensitive are shown expanded
+    #[serde(default)]
+    pub expand_content_warnings: Option<bool>,
+    /// ISO 639-1 codes of up to 10 languages to show in the feed, or an empty list for
+    /// all of them
+    #[serde(default)]
+    pub preferred_languages: Option<Vec<String>>,
+}
+
+/// Get the current user's settings
+#[utoipa::path(
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Settings of the current user", body = UserSettings),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[get("/users/me/settings")]
+pub async fn get_settings(req: HttpRequest, pool: web::Data<DbPool>) -> impl Responder {
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        UserSettings::load(&mut conn, user_id).map_err(|_| "Failed to load settings")
+    })
+    .await;
+
+    match result {
+        Ok(Ok(settings)) => HttpResponse::Ok().json(settings),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Change the current user's settings
+///
+/// Only the fields present in the request are changed.
+#[utoipa::path(
+    request_body = UpdateSettingsRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Settings updated successfully", body = UserSettings),
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[patch("/users/me/settings")]
+pub async fn update_settings(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    settings_req: web::Json<UpdateSettingsRequest>,
+) -> impl Responder {
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let preferred_languages = match settings_req
+        .preferred_languages
+        .as_ref()
+        .map(|languages| language::normalize_all(languages.iter().map(String::as_str)))
+        .transpose()
+    {
+        Ok(preferred_languages) => preferred_languages,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(json!({
+                "error": e
+            }));
+        }
+    };
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let mut settings = UserSettings::load(conn, user_id)?;
+            if let Some(require_alt_text) = settings_req.require_alt_text {
+                settings.require_alt_text = require_alt_text;
+            }
+            if let Some(default_visibility) = settings_req.default_visibility {
+                settings.default_visibility = default_visibility;
+            }
+            if let Some(expand_content_warnings) = settings_req.expand_content_warnings {
+                settings.expand_content_warnings = expand_content_warnings;
+            }
+            if let Some(preferred_languages) = preferred_languages {
+                settings.preferred_languages = preferred_languages;
+            }
+
+            // Users get a row the first time they change anything
+            diesel::insert_into(user_settings::table)
+                .values(&settings)
+                .on_conflict(user_settings::user_id)
+                .do_update()
+                .set(&settings)
+                .get_result::<UserSettings>(conn)
+        })
+        .map_err(|_| "Failed to update settings")
+    })
+    .await;
+
+    match result {
+        Ok(Ok(settings)) => HttpResponse::Ok().json(settings),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
@@ -0,0 +1,184 @@
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, post, web};
+use diesel::Connection;
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::controllers::post_controller::{CreatePostError, CreatePostRequest, insert_post};
+use crate::models::{post::Post, post_view::PostView, user::AuthedUserId};
+use crate::util::{content, db::DbPool};
+
+const MIN_THREAD_POSTS: usize = 2;
+const MAX_THREAD_POSTS: usize = 25;
+
+/// Used for API requests when creating a thread
+#[derive(Deserialize, ToSchema)]
+#[schema(example = json!({
+    "posts": [
+        { "content": "A thread about Rust 🧵" },
+        { "content": "First, ownership." },
+        { "content": "Then, borrowing." }
+    ]
+}))]
+pub struct CreateThreadRequest {
+    /// Between 2 and 25 posts, in order. Each post replies to the one before it, and
+    /// only the first can reply to another post. Threads cannot be scheduled.
+    pub posts: Vec<CreatePostRequest>,
+}
+
+/// Reason a thread cannot be created
+struct ThreadError {
+    /// Position of the post at fault in the request, if a single post is at fault
+    index: Option<usize>,
+    error: CreatePostError,
+}
+
+impl ThreadError {
+    /// Attributes an error to the post at `index`
+    fn at(index: usize) -> impl FnOnce(CreatePostError) -> ThreadError {
+        move |error| ThreadError {
+            index: Some(index),
+            error,
+        }
+    }
+
+    /// Builds the response explaining why the thread was not created, pointing at
+    /// the post at fault
+    fn into_response(self) -> HttpResponse {
+        let Some(index) = self.index else {
+            return self.error.into_response();
+        };
+
+        match self.error {
+            CreatePostError::InvalidContent(errors) => {
+                HttpResponse::UnprocessableEntity().json(json!({
+                    "error": "Invalid post content",
+                    "details": errors,
+                    "index": index
+                }))
+            }
+            CreatePostError::Invalid(e) => HttpResponse::BadRequest().json(json!({
+                "error": e,
+                "index": index
+            })),
+            CreatePostError::Forbidden(e) => HttpResponse::Forbidden().json(json!({
+                "error": e,
+                "index": index
+            })),
+            CreatePostError::Failed(e) => HttpResponse::InternalServerError().json(json!({
+                "error": e
+            })),
+        }
+    }
+}
+
+impl From<CreatePostError> for ThreadError {
+    fn from(error: CreatePostError) -> Self {
+        ThreadError { index: None, error }
+    }
+}
+
+impl From<diesel::result::Error> for ThreadError {
+    fn from(error: diesel::result::Error) -> Self {
+        CreatePostError::from(error).into()
+    }
+}
+
+/// Create a thread
+///
+/// All posts are validated and created together, as a chain of replies sharing one
+/// conversation: either every post is created or none is. Errors about a single post
+/// include its `index` in the request.
+#[utoipa::path(
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Unique key making retries safe, kept for `IDEMPOTENCY_KEY_TTL_HOURS`. Reusing it for a different request is refused with a 422.")
+    ),
+    request_body = CreateThreadRequest,
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 201, description = "Thread created successfully, in order", body = Vec<PostView>),
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not allowed to reply to the post"),
+        (status = 409, description = "A request with the same Idempotency-Key is still being handled"),
+        (status = 422, description = "Invalid post content, or Idempotency-Key reused for a different request"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[post("/threads")]
+pub async fn create_thread(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    thread_req: web::Json<CreateThreadRequest>,
+) -> impl Responder {
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let mut posts = thread_req.into_inner().posts;
+
+    if !(MIN_THREAD_POSTS..=MAX_THREAD_POSTS).contains(&posts.len()) {
+        return HttpResponse::BadRequest().json(json!({
+            "error": format!(
+                "A thread must have between {} and {} posts",
+                MIN_THREAD_POSTS, MAX_THREAD_POSTS
+            )
+        }));
+    }
+    if posts.iter().any(|post| post.publish_at.is_some()) {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "Threads cannot be scheduled"
+        }));
+    }
+    if posts
+        .iter()
+        .skip(1)
+        .any(|post| post.in_reply_to_id.is_some())
+    {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "Only the first post of a thread can reply to another post"
+        }));
+    }
+
+    // Check the content of every post before storing any
+    for (index, post) in posts.iter().enumerate() {
+        if let Err(errors) = content::validate(&post.content) {
+            return ThreadError {
+                index: Some(index),
+                error: CreatePostError::InvalidContent(errors),
+            }
+            .into_response();
+        }
+    }
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool
+            .get()
+            .map_err(|_| CreatePostError::Failed("Failed to get DB connection"))?;
+
+        let thread = conn.transaction::<_, ThreadError, _>(|conn| {
+            let mut thread: Vec<Post> = Vec::with_capacity(posts.len());
+            for (index, post_req) in posts.iter_mut().enumerate() {
+                if let Some(previous) = thread.last() {
+                    post_req.in_reply_to_id = Some(previous.id);
+                }
+                let post = insert_post(conn, user_id, post_req).map_err(ThreadError::at(index))?;
+                thread.push(post);
+            }
+            Ok(thread)
+        })?;
+
+        PostView::load_many(&mut conn, user_id, thread)
+            .map_err(|_| ThreadError::from(CreatePostError::Failed("Failed to load post details")))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(post_views)) => HttpResponse::Created().json(post_views),
+        Ok(Err(e)) => e.into_response(),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
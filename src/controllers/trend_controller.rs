@@ -0,0 +1,95 @@
+use actix_web::{HttpResponse, Responder, get, web};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::IntoParams;
+
+use crate::jobs::trends;
+use crate::models::trend::{Trend, TrendSnapshot, TrendsResponse};
+use crate::schema::{hashtags, trend_entries, trend_snapshots};
+use crate::util::db::DbPool;
+
+/// Query parameters for getting trends
+#[derive(Deserialize, IntoParams)]
+pub struct TrendsQuery {
+    /// Window to get trends for, in minutes (one of `TRENDS_WINDOWS_MINUTES`, defaults to the first)
+    pub window: Option<i32>,
+}
+
+/// Get the trending hashtags
+#[utoipa::path(
+    params(TrendsQuery),
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Latest trending hashtags for the window", body = TrendsResponse),
+        (status = 400, description = "Unsupported window"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[get("/trends")]
+pub async fn get_trends(pool: web::Data<DbPool>, query: web::Query<TrendsQuery>) -> impl Responder {
+    let windows = trends::windows();
+    let window_minutes = query.window.unwrap_or(windows[0]);
+
+    if !windows.contains(&window_minutes) {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "Unsupported window",
+            "supported_windows": windows
+        }));
+    }
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let snapshot = trend_snapshots::table
+            .filter(trend_snapshots::window_minutes.eq(window_minutes))
+            .order(trend_snapshots::computed_at.desc())
+            .first::<TrendSnapshot>(&mut conn)
+            .optional()
+            .map_err(|_| "Database error finding trends")?;
+
+        // Trends have not been computed yet
+        let Some(snapshot) = snapshot else {
+            return Ok::<_, &'static str>(TrendsResponse {
+                window_minutes,
+                computed_at: None,
+                trends: Vec::new(),
+            });
+        };
+
+        let trends = trend_entries::table
+            .inner_join(hashtags::table)
+            .filter(trend_entries::snapshot_id.eq(snapshot.id))
+            .order(trend_entries::rank.asc())
+            .select((
+                trend_entries::rank,
+                hashtags::name,
+                trend_entries::score,
+                trend_entries::post_count,
+                trend_entries::author_count,
+            ))
+            .load::<Trend>(&mut conn)
+            .map_err(|_| "Failed to load trends")?;
+
+        Ok(TrendsResponse {
+            window_minutes: snapshot.window_minutes,
+            computed_at: Some(snapshot.computed_at),
+            trends,
+        })
+    })
+    .await;
+
+    match result {
+        Ok(Ok(trends)) => HttpResponse::Ok().json(trends),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
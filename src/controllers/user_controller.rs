@@ -0,0 +1,195 @@
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, get, web};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use serde_json::json;
+
+use crate::models::{
+    pinned_post,
+    post::Post,
+    post_view::PostView,
+    user::{AuthedUserId, User, UserProfile},
+};
+use crate::schema::{posts, users};
+use crate::util::{
+    db::DbPool, language::LanguageFilter, pagination::Pagination, views::ViewRecorder,
+};
+
+/// Get the profile of a user
+#[utoipa::path(
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Profile of the user", body = UserProfile),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "User not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[get("/users/{id}")]
+pub async fn get_user(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    views: web::Data<ViewRecorder>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    let profile_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        let user = users::table
+            .find(profile_id)
+            .first::<User>(&mut conn)
+            .optional()
+            .map_err(|_| "Database error finding user")?;
+
+        let Some(user) = user else {
+            return Ok::<_, &'static str>(None);
+        };
+
+        let pinned = pinned_post::load_posts(&mut conn, user.id, user_id)
+            .map_err(|_| "Failed to load pinned posts")?;
+        let pinned_posts = PostView::load_many(&mut conn, user_id, pinned)
+            .map_err(|_| "Failed to load post details")?;
+
+        Ok(Some(UserProfile {
+            id: user.id,
+            username: user.username,
+            created_at: user.created_at,
+            pinned_posts,
+        }))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Some(profile))) => {
+            views.record_impressions(user_id, &profile.pinned_posts);
+            HttpResponse::Ok().json(profile)
+        }
+        Ok(Ok(None)) => HttpResponse::NotFound().json(json!({
+            "error": "User not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Get the posts of a user
+///
+/// The first page starts with the posts the user pinned, in order, followed by their
+/// other posts, most recent first. Pinned posts do not count towards `limit`.
+#[utoipa::path(
+    params(Pagination, LanguageFilter),
+    security(
+        ("bearer_auth" = [])
+    ),
+    responses(
+        (status = 200, description = "Posts of the user", body = Vec<PostView>),
+        (status = 400, description = "Unknown language code"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "User not found"),
+        (status = 500, description = "Server error")
+    )
+)]
+#[get("/users/{id}/posts")]
+pub async fn get_user_posts(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    views: web::Data<ViewRecorder>,
+    id: web::Path<i32>,
+    page: web::Query<Pagination>,
+    filter: web::Query<LanguageFilter>,
+) -> impl Responder {
+    let author_id = id.into_inner();
+
+    let user_id = req.extensions().get::<AuthedUserId>().unwrap().0;
+
+    let languages = match filter.languages() {
+        Ok(languages) => languages,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(json!({
+                "error": e
+            }));
+        }
+    };
+
+    // Use a web::block to offload database operations to a separate thread
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        // Check if user exists
+        let user_exists = users::table
+            .find(author_id)
+            .first::<User>(&mut conn)
+            .optional()
+            .map_err(|_| "Database error checking user")?;
+
+        if user_exists.is_none() {
+            return Ok::<_, &'static str>(None);
+        }
+
+        let pinned = pinned_post::load_posts(&mut conn, author_id, user_id)
+            .map_err(|_| "Failed to load pinned posts")?;
+        let pinned_ids: Vec<i32> = pinned.iter().map(|post| post.id).collect();
+
+        let mut authored = Post::visible_to(user_id)
+            .filter(posts::user_id.eq(author_id))
+            .filter(posts::id.ne_all(&pinned_ids));
+        if let Some(languages) = &languages {
+            authored = authored.filter(Post::is_in_languages(languages.clone(), false));
+        }
+
+        // Pinned posts are shown once, at the top
+        let mut timeline = if page.offset() == 0 {
+            pinned
+                .into_iter()
+                .filter(|post| {
+                    languages.as_ref().is_none_or(|languages| {
+                        post.lang
+                            .as_ref()
+                            .is_some_and(|lang| languages.contains(lang))
+                    })
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        timeline.extend(
+            authored
+                .order((posts::created_at.desc(), posts::id.desc()))
+                .limit(page.limit())
+                .offset(page.offset())
+                .load::<Post>(&mut conn)
+                .map_err(|_| "Failed to load posts")?,
+        );
+
+        let post_views = PostView::load_many(&mut conn, user_id, timeline)
+            .map_err(|_| "Failed to load post details")?;
+
+        Ok(Some(post_views))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Some(post_views))) => {
+            views.record_impressions(user_id, &post_views);
+            HttpResponse::Ok().json(post_views)
+        }
+        Ok(Ok(None)) => HttpResponse::NotFound().json(json!({
+            "error": "User not found"
+        })),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": e
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": e.to_string()
+        })),
+    }
+}
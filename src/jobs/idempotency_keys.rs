@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use actix_web::{rt, web};
+use diesel::dsl::{IntervalDsl, now};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+use crate::schema::idempotency_keys;
+use crate::util::{config, db::DbPool};
+
+// How often expired idempotency keys are checked for purging
+const RUN_EVERY: Duration = Duration::from_secs(60 * 60);
+
+/// Starts the job that removes idempotency keys, and the responses stored with them,
+/// once they are older than `IDEMPOTENCY_KEY_TTL_HOURS`
+pub fn spawn(pool: DbPool) {
+    let ttl_hours = config::env_or("IDEMPOTENCY_KEY_TTL_HOURS", 24);
+
+    rt::spawn(async move {
+        let mut interval = rt::time::interval(RUN_EVERY);
+        loop {
+            interval.tick().await;
+
+            let pool = pool.clone();
+            let result = web::block(move || {
+                let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+                diesel::delete(
+                    idempotency_keys::table
+                        .filter(idempotency_keys::created_at.lt(now - ttl_hours.hours())),
+                )
+                .execute(&mut conn)
+                .map_err(|_| "Failed to purge idempotency keys")
+            })
+            .await;
+
+            match result {
+                Ok(Ok(0)) => {}
+                Ok(Ok(purged)) => println!("Purged {} expired idempotency keys", purged),
+                Ok(Err(e)) => eprintln!("Idempotency key job failed: {}", e),
+                Err(e) => eprintln!("Idempotency key job failed: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,329 @@
+use std::time::Duration;
+
+use actix_web::{rt, web};
+use diesel::dsl::{IntervalDsl, now};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+
+use crate::schema::link_cards;
+use crate::util::{
+    config,
+    db::DbPool,
+    unfurl::{FetchError, PageCard, Unfurler},
+};
+
+// Cards fetched on each run at most
+const BATCH_SIZE: i64 = 10;
+
+// Claimed cards are fetched again after this long if the server fetching them
+// stopped before saving them
+const CLAIM_LEASE_MINUTES: i32 = 10;
+
+// Wait before fetching a card again after a failure that may go away, doubled after
+// each such failure in a row
+const RETRY_DELAY_SECONDS: i64 = 60;
+
+/// Starts the job that fetches the previews of posted URLs, checking every
+/// `LINK_CARDS_INTERVAL_SECONDS`
+///
+/// New URLs are fetched right away, then again every `LINK_CARDS_REFRESH_HOURS` so
+/// that cards follow changes to their pages. Fetches failing for a reason that may go
+/// away, such as a timeout or a server error, are tried again sooner, keeping the
+/// card fetched before. Pages that have no preview or cannot be fetched at all are
+/// remembered as such until the next refresh, and posts linking to them show no card.
+pub fn spawn(pool: DbPool) {
+    let run_every =
+        Duration::from_secs(config::env_or("LINK_CARDS_INTERVAL_SECONDS", 5_u64).max(1));
+    let refresh_hours = config::env_or("LINK_CARDS_REFRESH_HOURS", 168_i32).max(1);
+    let unfurler = Unfurler::from_env();
+
+    rt::spawn(async move {
+        let mut interval = rt::time::interval(run_every);
+        loop {
+            interval.tick().await;
+
+            let pool = pool.clone();
+            let unfurler = unfurler.clone();
+            let result = web::block(move || {
+                let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+                let cards = claim(&mut conn).map_err(|_| "Failed to claim link cards")?;
+                for card in &cards {
+                    let fetched = unfurler.fetch(&card.url);
+                    // The card is fetched again once its claim expires
+                    if save(&mut conn, card, fetched, refresh_hours).is_err() {
+                        eprintln!("Failed to save link card {}", card.id);
+                    }
+                }
+
+                Ok::<_, &'static str>(cards.len())
+            })
+            .await;
+
+            match result {
+                Ok(Ok(0)) => {}
+                Ok(Ok(fetched)) => println!("Fetched {} link cards", fetched),
+                Ok(Err(e)) => eprintln!("Link cards job failed: {}", e),
+                Err(e) => eprintln!("Link cards job failed: {}", e),
+            }
+        }
+    });
+}
+
+struct ClaimedCard {
+    id: i32,
+    url: String,
+    // Failures in a row that may go away
+    attempts: i32,
+}
+
+// Claims a batch of cards due to be fetched, new ones first, by pushing back their
+// next fetch by `CLAIM_LEASE_MINUTES`. Servers running this job at the same time
+// each get different cards, and cards whose server stopped are fetched again once
+// the lease is over.
+fn claim(conn: &mut PgConnection) -> QueryResult<Vec<ClaimedCard>> {
+    conn.transaction(|conn| {
+        let cards = link_cards::table
+            .filter(link_cards::next_fetch_at.le(now))
+            .order((
+                link_cards::fetched_at.is_not_null(),
+                link_cards::next_fetch_at,
+            ))
+            .limit(BATCH_SIZE)
+            .select((link_cards::id, link_cards::url, link_cards::attempts))
+            .for_update()
+            .skip_locked()
+            .load::<(i32, String, i32)>(conn)?;
+
+        let ids: Vec<i32> = cards.iter().map(|(id, _, _)| *id).collect();
+        diesel::update(link_cards::table.filter(link_cards::id.eq_any(&ids)))
+            .set(link_cards::next_fetch_at.eq(now + CLAIM_LEASE_MINUTES.minutes()))
+            .execute(conn)?;
+
+        Ok(cards
+            .into_iter()
+            .map(|(id, url, attempts)| ClaimedCard { id, url, attempts })
+            .collect())
+    })
+}
+
+// Saves what fetching a claimed card gave and when to fetch it next
+fn save(
+    conn: &mut PgConnection,
+    card: &ClaimedCard,
+    fetched: Result<Option<PageCard>, FetchError>,
+    refresh_hours: i32,
+) -> QueryResult<usize> {
+    let (page, error) = match fetched {
+        Ok(page) => (page, None),
+        Err(FetchError::Permanent(error)) => (None, Some(error)),
+        Err(FetchError::Transient(error)) => {
+            let delay = retry_delay_seconds(card.attempts, refresh_hours);
+            return diesel::update(link_cards::table.find(card.id))
+                .set((
+                    link_cards::fetch_error.eq(error),
+                    link_cards::attempts.eq(card.attempts + 1),
+                    link_cards::next_fetch_at.eq(now + delay.seconds()),
+                ))
+                .execute(conn);
+        }
+    };
+
+    diesel::update(link_cards::table.find(card.id))
+        .set((
+            link_cards::card_type.eq(page.as_ref().map(|page| &page.card_type)),
+            link_cards::title.eq(page.as_ref().map(|page| &page.title)),
+            link_cards::description.eq(page.as_ref().and_then(|page| page.description.as_ref())),
+            link_cards::image_url.eq(page.as_ref().and_then(|page| page.image_url.as_ref())),
+            link_cards::site_name.eq(page.as_ref().and_then(|page| page.site_name.as_ref())),
+            link_cards::fetch_error.eq(error),
+            link_cards::attempts.eq(0),
+            link_cards::fetched_at.eq(now),
+            link_cards::next_fetch_at.eq(now + refresh_hours.hours()),
+        ))
+        .execute(conn)
+}
+
+// How long to wait before fetching a card again after `attempts` failures in a row,
+// then one more, never waiting longer than until the next refresh
+fn retry_delay_seconds(attempts: i32, refresh_hours: i32) -> i64 {
+    let backoff = 2_i64.saturating_pow(attempts.clamp(0, 62) as u32);
+    RETRY_DELAY_SECONDS
+        .saturating_mul(backoff)
+        .min(i64::from(refresh_hours) * 3600)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    use crate::models::link_card::NewLinkCard;
+
+    #[test]
+    fn retries_back_off_until_the_next_refresh() {
+        let cases = [
+            (0, 60),
+            (1, 120),
+            (2, 240),
+            (5, 1920),
+            (10, 61440),
+            (11, 24 * 3600),
+            (1000, 24 * 3600),
+        ];
+
+        for (attempts, expected) in cases {
+            assert_eq!(
+                retry_delay_seconds(attempts, 24),
+                expected,
+                "{:?}",
+                attempts
+            );
+        }
+    }
+
+    // These tests need a database and are ignored by default. Run them with
+    // `DATABASE_URL=postgres://localhost/twitter_test cargo test -- --ignored`. Each
+    // runs in a transaction that is rolled back.
+    fn test_connection() -> PgConnection {
+        let database_url =
+            std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for database tests");
+        let mut conn =
+            PgConnection::establish(&database_url).expect("Failed to connect to the database");
+        conn.begin_test_transaction().unwrap();
+
+        // Keep the cards of other tests out of the way
+        diesel::update(link_cards::table)
+            .set(link_cards::next_fetch_at.eq(now + 1.days()))
+            .execute(&mut conn)
+            .unwrap();
+        conn
+    }
+
+    fn create_card(conn: &mut PgConnection, url: &str) -> i32 {
+        diesel::insert_into(link_cards::table)
+            .values(&NewLinkCard { url })
+            .returning(link_cards::id)
+            .get_result(conn)
+            .unwrap()
+    }
+
+    fn page(title: &str) -> PageCard {
+        PageCard {
+            card_type: "summary".to_string(),
+            title: title.to_string(),
+            description: None,
+            image_url: None,
+            site_name: None,
+        }
+    }
+
+    type CardState = (Option<String>, Option<String>, i32, bool);
+
+    // Title, error, attempts and whether the card is due, in minutes from now
+    fn state(conn: &mut PgConnection, id: i32, in_minutes: i32) -> CardState {
+        let (title, error, attempts, next_fetch_at) = link_cards::table
+            .find(id)
+            .select((
+                link_cards::title,
+                link_cards::fetch_error,
+                link_cards::attempts,
+                link_cards::next_fetch_at,
+            ))
+            .get_result::<(Option<String>, Option<String>, i32, NaiveDateTime)>(conn)
+            .unwrap();
+        let due = diesel::select((now + in_minutes.minutes()).ge(next_fetch_at))
+            .get_result(conn)
+            .unwrap();
+        (title, error, attempts, due)
+    }
+
+    fn claimed_ids(conn: &mut PgConnection) -> Vec<i32> {
+        claim(conn)
+            .unwrap()
+            .into_iter()
+            .map(|card| card.id)
+            .collect()
+    }
+
+    #[test]
+    #[ignore = "requires DATABASE_URL"]
+    fn claimed_cards_are_claimed_again_once_the_lease_is_over() {
+        let mut conn = test_connection();
+        let id = create_card(&mut conn, "https://example.com/claimed");
+
+        assert_eq!(claimed_ids(&mut conn), vec![id]);
+        // Another server, or this one after a crash, must not fetch it in the meantime
+        assert_eq!(claimed_ids(&mut conn), Vec::<i32>::new());
+        assert!(!state(&mut conn, id, CLAIM_LEASE_MINUTES - 1).3);
+        assert!(state(&mut conn, id, CLAIM_LEASE_MINUTES + 1).3);
+    }
+
+    #[test]
+    #[ignore = "requires DATABASE_URL"]
+    fn new_cards_are_claimed_before_refreshes() {
+        let mut conn = test_connection();
+        let stale = create_card(&mut conn, "https://example.com/stale");
+        diesel::update(link_cards::table.find(stale))
+            .set((
+                link_cards::fetched_at.eq((now - 8.days()).nullable()),
+                link_cards::next_fetch_at.eq(now - 1.days()),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        let new = create_card(&mut conn, "https://example.com/new");
+
+        assert_eq!(claimed_ids(&mut conn), vec![new, stale]);
+    }
+
+    #[test]
+    #[ignore = "requires DATABASE_URL"]
+    fn transient_failures_are_retried_with_backoff_keeping_the_card() {
+        let mut conn = test_connection();
+        let id = create_card(&mut conn, "https://example.com/flaky");
+        let mut card = claim(&mut conn).unwrap().pop().unwrap();
+
+        save(&mut conn, &card, Ok(Some(page("First"))), 24).unwrap();
+        assert_eq!(
+            state(&mut conn, id, 23 * 60),
+            (Some("First".to_string()), None, 0, false)
+        );
+        assert!(state(&mut conn, id, 24 * 60 + 1).3);
+
+        for (attempts, minutes) in [(1, 1), (2, 2), (3, 4)] {
+            let error = FetchError::Transient("Page is temporarily unavailable");
+            save(&mut conn, &card, Err(error), 24).unwrap();
+            card.attempts = attempts;
+
+            let (title, error, saved_attempts, due_before) = state(&mut conn, id, minutes - 1);
+            assert_eq!(title.as_deref(), Some("First"));
+            assert_eq!(error.as_deref(), Some("Page is temporarily unavailable"));
+            assert_eq!(saved_attempts, attempts);
+            assert!(!due_before, "{:?}", attempts);
+            assert!(state(&mut conn, id, minutes).3, "{:?}", attempts);
+        }
+
+        save(&mut conn, &card, Ok(Some(page("Second"))), 24).unwrap();
+        assert_eq!(
+            state(&mut conn, id, 23 * 60),
+            (Some("Second".to_string()), None, 0, false)
+        );
+    }
+
+    #[test]
+    #[ignore = "requires DATABASE_URL"]
+    fn permanent_failures_clear_the_card_until_the_next_refresh() {
+        let mut conn = test_connection();
+        let id = create_card(&mut conn, "https://example.com/gone");
+        let card = claim(&mut conn).unwrap().pop().unwrap();
+
+        save(&mut conn, &card, Ok(Some(page("Here"))), 24).unwrap();
+        let error = FetchError::Permanent("Page is not available");
+        save(&mut conn, &card, Err(error), 24).unwrap();
+        assert_eq!(
+            state(&mut conn, id, 23 * 60),
+            (None, Some("Page is not available".to_string()), 0, false)
+        );
+        assert!(state(&mut conn, id, 24 * 60 + 1).3);
+    }
+}
@@ -0,0 +1,7 @@
+// Export background jobs
+pub mod idempotency_keys;
+pub mod link_cards;
+pub mod post_retention;
+pub mod scheduled_posts;
+pub mod trends;
+pub mod view_counts;
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::{rt, web};
+use diesel::dsl::{IntervalDsl, now};
+use diesel::{Connection, ExpressionMethods, NullableExpressionMethods, QueryDsl, RunQueryDsl};
+
+use crate::models::media::Media;
+use crate::schema::posts;
+use crate::storage::{self, MediaStore};
+use crate::util::{config, db::DbPool};
+
+// How often deleted posts are checked for purging
+const RUN_EVERY: Duration = Duration::from_secs(60 * 60);
+
+/// Starts the job that permanently removes posts deleted more than
+/// `POST_RETENTION_DAYS` ago, along with their media files
+pub fn spawn(pool: DbPool, store: Arc<dyn MediaStore>) {
+    let retention_days = config::env_or("POST_RETENTION_DAYS", 30);
+
+    rt::spawn(async move {
+        let mut interval = rt::time::interval(RUN_EVERY);
+        loop {
+            interval.tick().await;
+
+            let pool = pool.clone();
+            let store = store.clone();
+            let result = web::block(move || {
+                let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+                // Likes, reposts, bookmarks, revisions and media rows go with the post
+                let (purged, media_keys) = conn
+                    .transaction::<_, diesel::result::Error, _>(|conn| {
+                        let post_ids = posts::table
+                            .filter(posts::deleted_at.lt((now - retention_days.days()).nullable()))
+                            .select(posts::id)
+                            .for_update()
+                            .load::<i32>(conn)?;
+                        let media_keys = Media::storage_keys_for_posts(conn, &post_ids)?;
+
+                        let purged =
+                            diesel::delete(posts::table.filter(posts::id.eq_any(&post_ids)))
+                                .execute(conn)?;
+                        Ok((purged, media_keys))
+                    })
+                    .map_err(|_| "Failed to purge deleted posts")?;
+
+                // Files are only removed once their rows are gone for good
+                storage::delete_all(store.as_ref(), &media_keys);
+
+                Ok::<_, &'static str>(purged)
+            })
+            .await;
+
+            match result {
+                Ok(Ok(0)) => {}
+                Ok(Ok(purged)) => println!("Purged {} deleted posts", purged),
+                Ok(Err(e)) => eprintln!("Post retention job failed: {}", e),
+                Err(e) => eprintln!("Post retention job failed: {}", e),
+            }
+        }
+    });
+}
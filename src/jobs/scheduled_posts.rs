@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use actix_web::{rt, web};
+use diesel::dsl::now;
+use diesel::{ExpressionMethods, NullableExpressionMethods, QueryDsl, RunQueryDsl};
+
+use crate::schema::posts;
+use crate::util::{config, db::DbPool};
+
+/// Starts the job that publishes scheduled posts once they are due, checking every
+/// `SCHEDULED_POSTS_INTERVAL_SECONDS`
+///
+/// The schedule lives in the database, so posts that became due while the server was
+/// down are published on the first run after it starts.
+pub fn spawn(pool: DbPool) {
+    let run_every =
+        Duration::from_secs(config::env_or("SCHEDULED_POSTS_INTERVAL_SECONDS", 15_u64).max(1));
+
+    rt::spawn(async move {
+        let mut interval = rt::time::interval(run_every);
+        loop {
+            interval.tick().await;
+
+            let pool = pool.clone();
+            let result = web::block(move || {
+                let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+                // A single conditional update, so each post is published exactly once
+                // even with several servers running this job. Published posts are
+                // dated from when they actually go live.
+                diesel::update(
+                    posts::table
+                        .filter(posts::publish_at.le(now.nullable()))
+                        .filter(posts::deleted_at.is_null()),
+                )
+                .set((
+                    posts::publish_at.eq(None::<chrono::NaiveDateTime>),
+                    posts::created_at.eq(now),
+                ))
+                .execute(&mut conn)
+                .map_err(|_| "Failed to publish scheduled posts")
+            })
+            .await;
+
+            match result {
+                Ok(Ok(0)) => {}
+                Ok(Ok(published)) => println!("Published {} scheduled posts", published),
+                Ok(Err(e)) => eprintln!("Scheduled posts job failed: {}", e),
+                Err(e) => eprintln!("Scheduled posts job failed: {}", e),
+            }
+        }
+    });
+}
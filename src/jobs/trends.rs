@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use actix_web::{rt, web};
+use chrono::NaiveDateTime;
+use diesel::dsl::{IntervalDsl, now};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+
+use crate::models::{
+    post::{Post, Visibility},
+    trend::{NewTrendEntry, NewTrendSnapshot},
+};
+use crate::schema::{post_hashtags, posts, trend_entries, trend_snapshots};
+use crate::util::{config, db::DbPool};
+
+// The baseline a window is compared with spans this many windows before it
+const BASELINE_WINDOWS: i32 = 6;
+
+// A single author can add at most this much to the score of a hashtag
+const MAX_AUTHOR_WEIGHT: f64 = 1.0;
+
+// Snapshots older than this are removed
+const SNAPSHOT_RETENTION_DAYS: i32 = 7;
+
+/// Windows trends are computed for, in minutes, from `TRENDS_WINDOWS_MINUTES`
+pub fn windows() -> Vec<i32> {
+    let windows = config::env_or("TRENDS_WINDOWS_MINUTES", "60,1440".to_string())
+        .split(',')
+        .filter_map(|window| window.trim().parse().ok())
+        .filter(|window| *window > 0)
+        .collect::<Vec<i32>>();
+
+    if windows.is_empty() {
+        vec![60]
+    } else {
+        windows
+    }
+}
+
+/// Starts the job that computes trending hashtags every `TRENDS_INTERVAL_MINUTES`
+pub fn spawn(pool: DbPool) {
+    let run_every =
+        Duration::from_secs(60 * config::env_or("TRENDS_INTERVAL_MINUTES", 5_u64).max(1));
+
+    rt::spawn(async move {
+        let mut interval = rt::time::interval(run_every);
+        loop {
+            interval.tick().await;
+
+            let pool = pool.clone();
+            let result = web::block(move || {
+                let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+                compute_snapshots(&mut conn).map_err(|_| "Failed to compute trends")
+            })
+            .await;
+
+            match result {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => eprintln!("Trends job failed: {}", e),
+                Err(e) => eprintln!("Trends job failed: {}", e),
+            }
+        }
+    });
+}
+
+// Computes and stores a snapshot for every window, dropping old snapshots
+fn compute_snapshots(conn: &mut PgConnection) -> QueryResult<()> {
+    // Use the database clock, which also set posts.created_at
+    let computed_at = diesel::select(now).get_result::<NaiveDateTime>(conn)?;
+    let limit = config::env_or("TRENDS_LIMIT", 10);
+    let min_authors = config::env_or("TRENDS_MIN_AUTHORS", 3);
+
+    conn.transaction(|conn| {
+        for window_minutes in windows() {
+            let trends = compute_trends(conn, window_minutes, computed_at, min_authors)?;
+
+            let snapshot_id = diesel::insert_into(trend_snapshots::table)
+                .values(&NewTrendSnapshot {
+                    window_minutes,
+                    computed_at,
+                })
+                .returning(trend_snapshots::id)
+                .get_result::<i32>(conn)?;
+
+            let entries = trends
+                .into_iter()
+                .take(limit)
+                .enumerate()
+                .map(|(index, trend)| NewTrendEntry {
+                    snapshot_id,
+                    rank: index as i32 + 1,
+                    hashtag_id: trend.hashtag_id,
+                    score: trend.score,
+                    post_count: trend.post_count,
+                    author_count: trend.author_count,
+                })
+                .collect::<Vec<_>>();
+            diesel::insert_into(trend_entries::table)
+                .values(&entries)
+                .execute(conn)?;
+        }
+
+        diesel::delete(
+            trend_snapshots::table
+                .filter(trend_snapshots::computed_at.lt(now - SNAPSHOT_RETENTION_DAYS.days())),
+        )
+        .execute(conn)?;
+
+        Ok(())
+    })
+}
+
+#[derive(Debug)]
+struct ScoredHashtag {
+    hashtag_id: i32,
+    score: f64,
+    post_count: i32,
+    author_count: i32,
+}
+
+#[derive(Default)]
+struct HashtagActivity {
+    // Decayed weight of the recent posts of each author
+    recent_by_author: HashMap<i32, f64>,
+    recent_posts: i32,
+    // Number of baseline posts of each author
+    baseline_by_author: HashMap<i32, f64>,
+}
+
+// Loads the hashtags of the live public posts of the window and its baseline, and
+// scores them
+fn compute_trends(
+    conn: &mut PgConnection,
+    window_minutes: i32,
+    at: NaiveDateTime,
+    min_authors: usize,
+) -> QueryResult<Vec<ScoredHashtag>> {
+    let window = chrono::Duration::minutes(window_minutes.into());
+    let since = at - window * (BASELINE_WINDOWS + 1);
+
+    let rows = post_hashtags::table
+        .inner_join(posts::table)
+        .filter(Post::is_live())
+        .filter(posts::visibility.eq(Visibility::Public))
+        .filter(posts::created_at.ge(since))
+        .filter(posts::created_at.le(at))
+        .select((post_hashtags::hashtag_id, posts::user_id, posts::created_at))
+        .load::<(i32, i32, NaiveDateTime)>(conn)?;
+
+    Ok(score_hashtags(rows, window_minutes, at, min_authors))
+}
+
+// Scores hashtags by how much their recent volume exceeds their usual volume, from
+// `(hashtag_id, user_id, created_at)` rows of the window and its baseline
+//
+// Recent posts are weighted down as they age (half-life of half the window) and each
+// author contributes at most `MAX_AUTHOR_WEIGHT`, so a single account posting the
+// same hashtag over and over cannot make it trend.
+fn score_hashtags(
+    rows: impl IntoIterator<Item = (i32, i32, NaiveDateTime)>,
+    window_minutes: i32,
+    at: NaiveDateTime,
+    min_authors: usize,
+) -> Vec<ScoredHashtag> {
+    let window = chrono::Duration::minutes(window_minutes.into());
+    let window_seconds = window.num_seconds() as f64;
+    let half_life = window_seconds / 2.0;
+    let mut activity: HashMap<i32, HashtagActivity> = HashMap::new();
+    for (hashtag_id, user_id, created_at) in rows {
+        let age = (at - created_at).num_milliseconds() as f64 / 1000.0;
+        let hashtag = activity.entry(hashtag_id).or_default();
+
+        if age < window_seconds {
+            *hashtag.recent_by_author.entry(user_id).or_default() += 0.5_f64.powf(age / half_life);
+            hashtag.recent_posts += 1;
+        } else {
+            *hashtag.baseline_by_author.entry(user_id).or_default() += 1.0;
+        }
+    }
+
+    let mut trends = activity
+        .into_iter()
+        .filter(|(_, hashtag)| hashtag.recent_by_author.len() >= min_authors)
+        .filter_map(|(hashtag_id, hashtag)| {
+            let recent: f64 = hashtag
+                .recent_by_author
+                .values()
+                .map(|weight| weight.min(MAX_AUTHOR_WEIGHT))
+                .sum();
+            // Average volume per window over the baseline, with the same per-author cap
+            let baseline: f64 = hashtag
+                .baseline_by_author
+                .values()
+                .map(|count| count.min(f64::from(BASELINE_WINDOWS) * MAX_AUTHOR_WEIGHT))
+                .sum::<f64>()
+                / f64::from(BASELINE_WINDOWS);
+
+            let score = (recent - baseline) / (baseline + 1.0).sqrt();
+            (score > 0.0).then_some(ScoredHashtag {
+                hashtag_id,
+                score,
+                post_count: hashtag.recent_posts,
+                author_count: hashtag.recent_by_author.len() as i32,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    trends.sort_by(|a, b| b.score.total_cmp(&a.score));
+    trends
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASHTAG: i32 = 1;
+    const OTHER_HASHTAG: i32 = 2;
+
+    fn at() -> NaiveDateTime {
+        chrono::DateTime::from_timestamp(1_750_000_000, 0)
+            .unwrap()
+            .naive_utc()
+    }
+
+    // A post with `hashtag_id` by `user_id`, `minutes_ago` before `at()`
+    fn row(hashtag_id: i32, user_id: i32, minutes_ago: i64) -> (i32, i32, NaiveDateTime) {
+        (
+            hashtag_id,
+            user_id,
+            at() - chrono::Duration::minutes(minutes_ago),
+        )
+    }
+
+    fn score_of(trends: &[ScoredHashtag], hashtag_id: i32) -> Option<f64> {
+        trends
+            .iter()
+            .find(|trend| trend.hashtag_id == hashtag_id)
+            .map(|trend| trend.score)
+    }
+
+    #[test]
+    fn one_author_counts_at_most_max_author_weight() {
+        // Three authors posting once, then one of them posting 50 more times
+        let once = vec![row(HASHTAG, 1, 0), row(HASHTAG, 2, 0), row(HASHTAG, 3, 0)];
+        let mut spammed = once.clone();
+        spammed.extend(std::iter::repeat_n(row(HASHTAG, 1, 0), 50));
+        let trends = score_hashtags(once, 60, at(), 3);
+        let spammed_trends = score_hashtags(spammed, 60, at(), 3);
+
+        assert_eq!(spammed_trends[0].post_count, 53);
+        assert_eq!(spammed_trends[0].author_count, 3);
+        assert_eq!(spammed_trends[0].score, trends[0].score);
+        assert_eq!(trends[0].score, 3.0 * MAX_AUTHOR_WEIGHT);
+    }
+
+    #[test]
+    fn steady_baseline_does_not_trend() {
+        // Four authors post the first hashtag in every window, while the second one is new
+        let mut rows = Vec::new();
+        for window in 0..=BASELINE_WINDOWS as i64 {
+            for user_id in 1..=4 {
+                rows.push(row(HASHTAG, user_id, window * 60 + 1));
+            }
+        }
+        for user_id in 1..=4 {
+            rows.push(row(OTHER_HASHTAG, user_id, 1));
+        }
+
+        let trends = score_hashtags(rows, 60, at(), 3);
+        assert_eq!(score_of(&trends, HASHTAG), None);
+        assert!(score_of(&trends, OTHER_HASHTAG).unwrap() > 0.0);
+    }
+
+    #[test]
+    fn min_authors_filters_out_hashtags_with_few_authors() {
+        let rows = vec![
+            row(HASHTAG, 1, 0),
+            row(HASHTAG, 2, 0),
+            row(OTHER_HASHTAG, 1, 0),
+            row(OTHER_HASHTAG, 2, 0),
+            row(OTHER_HASHTAG, 3, 0),
+        ];
+
+        let cases = [
+            (1, vec![HASHTAG, OTHER_HASHTAG]),
+            (3, vec![OTHER_HASHTAG]),
+            (4, vec![]),
+        ];
+        for (min_authors, expected) in cases {
+            let mut hashtags = score_hashtags(rows.clone(), 60, at(), min_authors)
+                .into_iter()
+                .map(|trend| trend.hashtag_id)
+                .collect::<Vec<_>>();
+            hashtags.sort();
+            assert_eq!(hashtags, expected, "{:?}", min_authors);
+        }
+    }
+
+    #[test]
+    fn older_recent_posts_count_for_less() {
+        // The same authors, posting now and half a window ago
+        let rows = (1..=3)
+            .flat_map(|user_id| [row(HASHTAG, user_id, 0), row(OTHER_HASHTAG, user_id, 30)])
+            .collect::<Vec<_>>();
+
+        let trends = score_hashtags(rows, 60, at(), 3);
+        assert_eq!(trends[0].hashtag_id, HASHTAG);
+        assert_eq!(score_of(&trends, HASHTAG), Some(3.0));
+        assert_eq!(score_of(&trends, OTHER_HASHTAG), Some(1.5));
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::{rt, web};
+use chrono::{DurationRound, NaiveDateTime, TimeDelta};
+use diesel::dsl::now;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+
+use crate::models::view_count::{NewViewCount, NewViewReceipt};
+use crate::schema::{post_view_counts, post_view_receipts, posts};
+use crate::util::{
+    config,
+    db::DbPool,
+    views::{PendingView, ViewKind, ViewRecorder},
+};
+
+// Rows inserted per statement, well within the limit on bind parameters
+const CHUNK_SIZE: usize = 5_000;
+
+/// Starts the job that writes the views collected by `recorder` every
+/// `VIEWS_FLUSH_INTERVAL_SECONDS`
+///
+/// Views are counted in the hour they are written. A user viewing a post again in
+/// the same `VIEWS_DEDUP_WINDOW_MINUTES` window is not counted again.
+pub fn spawn(pool: DbPool, recorder: Arc<ViewRecorder>) {
+    let run_every =
+        Duration::from_secs(config::env_or("VIEWS_FLUSH_INTERVAL_SECONDS", 10_u64).max(1));
+    let window_minutes = config::env_or("VIEWS_DEDUP_WINDOW_MINUTES", 30).max(1);
+
+    rt::spawn(async move {
+        let mut interval = rt::time::interval(run_every);
+        loop {
+            interval.tick().await;
+
+            let views = recorder.take();
+            if views.is_empty() {
+                continue;
+            }
+
+            let pool = pool.clone();
+            let batch = views.clone();
+            let result = web::block(move || {
+                let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+                write_views(&mut conn, &batch, window_minutes).map_err(|_| "Failed to write views")
+            })
+            .await;
+
+            match result {
+                Ok(Ok(())) => {}
+                // Try again on the next run
+                Ok(Err(e)) => {
+                    eprintln!("View counts job failed: {}", e);
+                    recorder.record(views);
+                }
+                Err(e) => {
+                    eprintln!("View counts job failed: {}", e);
+                    recorder.record(views);
+                }
+            }
+        }
+    });
+}
+
+// Adds the views not counted yet in the current window to the counts of the current
+// hour
+fn write_views(
+    conn: &mut PgConnection,
+    views: &[PendingView],
+    window_minutes: i64,
+) -> QueryResult<()> {
+    // Use the database clock, which also set posts.created_at
+    let written_at = diesel::select(now).get_result::<NaiveDateTime>(conn)?;
+    let hour = truncate(written_at, TimeDelta::hours(1));
+    let window_start = truncate(written_at, TimeDelta::minutes(window_minutes));
+
+    conn.transaction(|conn| {
+        // Posts purged since they were viewed are left out
+        let post_ids: Vec<i32> = views.iter().map(|view| view.post_id).collect();
+        let existing: HashSet<i32> = posts::table
+            .filter(posts::id.eq_any(&post_ids))
+            .select(posts::id)
+            .load::<i32>(conn)?
+            .into_iter()
+            .collect();
+
+        let receipts: Vec<NewViewReceipt> = views
+            .iter()
+            .filter(|view| existing.contains(&view.post_id))
+            .map(|view| NewViewReceipt {
+                post_id: view.post_id,
+                viewer_id: view.viewer_id,
+                kind: view.kind.as_str(),
+                window_start,
+            })
+            .collect();
+
+        // Only the receipts inserted are views not counted yet
+        let mut counts: HashMap<i32, NewViewCount> = HashMap::new();
+        for chunk in receipts.chunks(CHUNK_SIZE) {
+            let counted = diesel::insert_into(post_view_receipts::table)
+                .values(chunk)
+                .on_conflict_do_nothing()
+                .returning((post_view_receipts::post_id, post_view_receipts::kind))
+                .get_results::<(i32, String)>(conn)?;
+
+            for (post_id, kind) in counted {
+                let count = counts.entry(post_id).or_insert(NewViewCount {
+                    post_id,
+                    hour,
+                    impressions: 0,
+                    detail_views: 0,
+                });
+                if kind == ViewKind::Detail.as_str() {
+                    count.detail_views += 1;
+                } else {
+                    count.impressions += 1;
+                }
+            }
+        }
+
+        let counts: Vec<NewViewCount> = counts.into_values().collect();
+        for chunk in counts.chunks(CHUNK_SIZE) {
+            diesel::insert_into(post_view_counts::table)
+                .values(chunk)
+                .on_conflict((post_view_counts::post_id, post_view_counts::hour))
+                .do_update()
+                .set(
+                    (
+                        post_view_counts::impressions
+                            .eq(post_view_counts::impressions
+                                + excluded(post_view_counts::impressions)),
+                        post_view_counts::detail_views.eq(post_view_counts::detail_views
+                            + excluded(post_view_counts::detail_views)),
+                    ),
+                )
+                .execute(conn)?;
+        }
+
+        // Receipts of past windows cannot match new views anymore
+        diesel::delete(
+            post_view_receipts::table.filter(post_view_receipts::window_start.lt(window_start)),
+        )
+        .execute(conn)?;
+
+        Ok(())
+    })
+}
+
+fn truncate(time: NaiveDateTime, step: TimeDelta) -> NaiveDateTime {
+    time.duration_trunc(step).unwrap_or(time)
+}
@@ -1,21 +1,49 @@
 mod controllers;
+mod jobs;
 mod middlewares;
 mod models;
 mod schema;
+mod storage;
 mod util;
 
+use actix_multipart::form::MultipartFormConfig;
 use actix_web::{App, HttpServer, middleware::Logger, web};
 use dotenv::dotenv;
 use std::env;
+use std::sync::Arc;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use controllers::{
+    analytics_controller::get_post_analytics,
     auth_controller::{login, register},
-    post_controller::{create_post, delete_post, get_all_posts, get_post_by_id, update_post},
+    bookmark_controller::{
+        bookmark_post, create_bookmark_folder, delete_bookmark_folder, get_bookmark_folders,
+        get_bookmarks, remove_bookmark,
+    },
+    draft_controller::{create_draft, delete_draft, get_drafts, publish_draft, update_draft},
+    follow_controller::{follow_user, unfollow_user},
+    hashtag_controller::get_hashtag_posts,
+    like_controller::{get_post_likes, get_user_likes, like_post, unlike_post},
+    media_controller::{get_media_file, update_media, upload_error, upload_media},
+    mention_controller::get_my_mentions,
+    moderation_controller::{get_deleted_posts, mark_post_sensitive},
+    pin_controller::{pin_post, reorder_pins, unpin_post},
+    poll_controller::vote_in_poll,
+    post_controller::{
+        create_post, delete_post, get_all_posts, get_post_by_id, get_post_history, restore_post,
+        update_content_warning, update_post, update_post_language, update_post_visibility,
+        update_reply_audience,
+    },
+    repost_controller::{repost_post, undo_repost},
+    scheduled_post_controller::{cancel_scheduled_post, get_scheduled_posts, reschedule_post},
+    settings_controller::{get_settings, update_settings},
+    thread_controller::create_thread,
+    trend_controller::get_trends,
+    user_controller::{get_user, get_user_posts},
 };
-use middlewares::auth_middleware::AuthMiddleware;
-use util::db;
+use middlewares::{auth_middleware::AuthMiddleware, idempotency_middleware::IdempotencyMiddleware};
+use util::{config, db, views::ViewRecorder};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -25,6 +53,21 @@ async fn main() -> std::io::Result<()> {
     // Set up database connection pool
     let pool = db::establish_connection_pool();
 
+    // Set up the store for uploaded media
+    let media_store = storage::from_env();
+    let max_upload_bytes = config::env_or("MEDIA_MAX_UPLOAD_BYTES", 5 * 1024 * 1024);
+
+    // Set up the buffer views are counted in
+    let view_recorder = Arc::new(ViewRecorder::default());
+
+    // Start background jobs
+    jobs::idempotency_keys::spawn(pool.clone());
+    jobs::link_cards::spawn(pool.clone());
+    jobs::post_retention::spawn(pool.clone(), media_store.clone());
+    jobs::scheduled_posts::spawn(pool.clone());
+    jobs::trends::spawn(pool.clone());
+    jobs::view_counts::spawn(pool.clone(), view_recorder.clone());
+
     // Optional: Log the port we're running on
     let port = env::var("PORT").unwrap_or_else(|_| "8080".to_string());
     let host = env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
@@ -38,29 +81,88 @@ async fn main() -> std::io::Result<()> {
         let auth_middleware = AuthMiddleware::new()
             .ignore("/auth/register")
             .ignore("/auth/login")
+            .ignore("/media/files/")
             .ignore("/swagger-ui")
             .ignore("/api-docs/openapi.json");
 
         App::new()
             // Add database connection pool to app state
             .app_data(web::Data::new(pool.clone()))
+            // Add media store and upload limits to app state
+            .app_data(web::Data::from(media_store.clone()))
+            .app_data(
+                MultipartFormConfig::default()
+                    .total_limit(max_upload_bytes)
+                    .memory_limit(max_upload_bytes)
+                    .error_handler(upload_error),
+            )
+            // Add view recorder to app state
+            .app_data(web::Data::from(view_recorder.clone()))
             // Add logging middleware
             .wrap(Logger::default())
             // Public routes (no auth required)
             .service(register)
             .service(login)
+            .service(get_media_file)
             .service(
                 SwaggerUi::new("/swagger-ui/{_:.*}")
                     .url("/api-docs/openapi.json", util::api_doc::ApiDoc::openapi()),
             )
+            // Replay retried requests sent with an Idempotency-Key, once authenticated
+            .wrap(IdempotencyMiddleware::new().body_limit(max_upload_bytes))
             .wrap(auth_middleware)
             // Public endpoint to get all posts
             .service(get_all_posts)
             .service(get_post_by_id)
+            .service(get_post_history)
             // Protected routes (auth required)
             .service(create_post)
+            .service(create_thread)
             .service(update_post)
             .service(delete_post)
+            .service(restore_post)
+            .service(update_post_visibility)
+            .service(update_reply_audience)
+            .service(update_content_warning)
+            .service(update_post_language)
+            .service(get_post_analytics)
+            .service(get_scheduled_posts)
+            .service(reschedule_post)
+            .service(cancel_scheduled_post)
+            .service(get_drafts)
+            .service(create_draft)
+            .service(update_draft)
+            .service(delete_draft)
+            .service(publish_draft)
+            .service(repost_post)
+            .service(undo_repost)
+            .service(vote_in_poll)
+            .service(upload_media)
+            .service(update_media)
+            .service(like_post)
+            .service(unlike_post)
+            .service(get_post_likes)
+            .service(get_user_likes)
+            .service(get_my_mentions)
+            .service(follow_user)
+            .service(unfollow_user)
+            .service(get_user)
+            .service(get_user_posts)
+            .service(pin_post)
+            .service(unpin_post)
+            .service(reorder_pins)
+            .service(get_settings)
+            .service(update_settings)
+            .service(bookmark_post)
+            .service(remove_bookmark)
+            .service(get_bookmarks)
+            .service(get_bookmark_folders)
+            .service(create_bookmark_folder)
+            .service(delete_bookmark_folder)
+            .service(get_deleted_posts)
+            .service(mark_post_sensitive)
+            .service(get_hashtag_posts)
+            .service(get_trends)
     })
     .bind(&bind_address)?
     .run()
@@ -0,0 +1,715 @@
+use actix_web::{
+    Error, HttpMessage, HttpResponse,
+    body::{self, BoxBody, MessageBody},
+    dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+    error::ErrorInternalServerError,
+    http::{Method, StatusCode, header},
+    rt, web,
+};
+use diesel::dsl::{IntervalDsl, now};
+use diesel::{BoolExpressionMethods, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use futures_util::StreamExt;
+use futures_util::future::{LocalBoxFuture, Ready, ready};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::models::{
+    idempotency_key::{NewIdempotencyKey, StoredRequest},
+    user::AuthedUserId,
+};
+use crate::schema::idempotency_keys;
+use crate::util::{config, db::DbPool};
+
+// Header clients send to make retrying a request safe
+const IDEMPOTENCY_KEY: &str = "Idempotency-Key";
+// Header set on responses replayed from an earlier request
+const IDEMPOTENT_REPLAYED: &str = "Idempotent-Replayed";
+const MAX_KEY_LENGTH: usize = 255;
+// Claims are refreshed this often while their request is being handled
+const CLAIM_REFRESH: Duration = Duration::from_secs(60);
+// Claims that have not been refreshed for this long are given up, as the server that
+// was handling the request stopped before it could store or release it
+const CLAIM_LEASE_MINUTES: i32 = 5;
+// Largest body buffered by default, the same as the default limit of JSON bodies
+const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// Outcome of claiming an idempotency key for a request
+enum Claim {
+    /// The key is new, so the request must be handled
+    Claimed,
+    /// The same request was already handled, and this is its response
+    Replay(StoredRequest),
+    /// The key was already used for a different request
+    Mismatch,
+    /// The first request with the key is still being handled
+    InProgress,
+}
+
+// Idempotency middleware factory
+//
+// Must be wrapped inside the auth middleware, since keys belong to users.
+pub struct IdempotencyMiddleware {
+    pub ttl_hours: i32,
+    pub body_limit: usize,
+}
+
+impl IdempotencyMiddleware {
+    pub fn new() -> Self {
+        Self {
+            ttl_hours: config::env_or("IDEMPOTENCY_KEY_TTL_HOURS", 24),
+            body_limit: DEFAULT_BODY_LIMIT,
+        }
+    }
+
+    // Requests with larger bodies are refused instead of being buffered
+    pub fn body_limit(mut self, body_limit: usize) -> Self {
+        self.body_limit = body_limit;
+        self
+    }
+}
+
+impl Default for IdempotencyMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for IdempotencyMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Transform = IdempotencyMiddlewareService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(IdempotencyMiddlewareService {
+            service: Rc::new(service),
+            ttl_hours: self.ttl_hours,
+            body_limit: self.body_limit,
+        }))
+    }
+}
+
+pub struct IdempotencyMiddlewareService<S> {
+    service: Rc<S>,
+    ttl_hours: i32,
+    body_limit: usize,
+}
+
+impl<S, B> Service<ServiceRequest> for IdempotencyMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let ttl_hours = self.ttl_hours;
+        let body_limit = self.body_limit;
+
+        // Only mutating requests of signed in users sending a key are idempotent
+        let is_mutating = matches!(
+            *req.method(),
+            Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+        );
+        let user_id = req.extensions().get::<AuthedUserId>().map(|user| user.0);
+        let key = req
+            .headers()
+            .get(IDEMPOTENCY_KEY)
+            .map(|key| key.to_str().map(str::to_string));
+        let pool = req.app_data::<web::Data<DbPool>>().cloned();
+
+        let (true, Some(user_id), Some(key), Some(pool)) = (is_mutating, user_id, key, pool) else {
+            return Box::pin(async move {
+                let res = service.call(req).await?;
+                Ok(res.map_into_boxed_body())
+            });
+        };
+
+        Box::pin(async move {
+            let key = match key {
+                Ok(key) if !key.is_empty() && key.len() <= MAX_KEY_LENGTH => key,
+                _ => {
+                    return Ok(req.into_response(HttpResponse::BadRequest().json(json!({
+                        "error": "Idempotency-Key must be between 1 and 255 visible characters"
+                    }))));
+                }
+            };
+
+            // Read the body to fingerprint the request, then hand it back to the handler
+            let content_length = req
+                .headers()
+                .get(header::CONTENT_LENGTH)
+                .and_then(|content_length| content_length.to_str().ok())
+                .and_then(|content_length| content_length.parse::<usize>().ok());
+            if content_length.is_some_and(|content_length| content_length > body_limit) {
+                return Ok(req.into_response(body_too_large()));
+            }
+
+            let mut payload = req.take_payload();
+            let mut request_body = web::BytesMut::new();
+            while let Some(chunk) = payload.next().await {
+                let chunk = chunk?;
+                if request_body.len() + chunk.len() > body_limit {
+                    return Ok(req.into_response(body_too_large()));
+                }
+                request_body.extend_from_slice(&chunk);
+            }
+            let request_body = request_body.freeze();
+            let fingerprint = fingerprint(&req, &request_body);
+            req.set_payload(Payload::from(request_body));
+
+            let claimed = {
+                let pool = pool.clone();
+                let key = key.clone();
+                web::block(move || claim(&pool, user_id, &key, &fingerprint, ttl_hours)).await
+            };
+
+            let error = match claimed {
+                Ok(Ok(Claim::Claimed)) => None,
+                Ok(Ok(Claim::Replay(stored))) => {
+                    return Ok(req.into_response(replay(stored)));
+                }
+                Ok(Ok(Claim::Mismatch)) => Some(HttpResponse::UnprocessableEntity().json(json!({
+                    "error": "Idempotency-Key was already used for a different request"
+                }))),
+                Ok(Ok(Claim::InProgress)) => Some(HttpResponse::Conflict().json(json!({
+                    "error": "A request with this Idempotency-Key is still being handled"
+                }))),
+                Ok(Err(e)) => Some(HttpResponse::InternalServerError().json(json!({
+                    "error": e
+                }))),
+                Err(e) => Some(HttpResponse::InternalServerError().json(json!({
+                    "error": e.to_string()
+                }))),
+            };
+            if let Some(error) = error {
+                return Ok(req.into_response(error));
+            }
+
+            let heartbeat = keep_claimed(pool.clone(), user_id, key.clone());
+            let res = service.call(req).await;
+            heartbeat.abort();
+
+            let res = match res {
+                Ok(res) => res,
+                Err(e) => {
+                    release(pool, user_id, key).await;
+                    return Err(e);
+                }
+            };
+
+            // Server errors are not kept, so the request can be retried
+            if res.status().is_server_error() {
+                release(pool, user_id, key).await;
+                return Ok(res.map_into_boxed_body());
+            }
+
+            let status_code = i32::from(res.status().as_u16());
+            let content_type = res
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|content_type| content_type.to_str().ok())
+                .map(str::to_string);
+
+            let (req, res) = res.into_parts();
+            let (res, response_body) = res.into_parts();
+            let response_body = match body::to_bytes(response_body).await {
+                Ok(response_body) => response_body,
+                Err(_) => {
+                    release(pool, user_id, key).await;
+                    return Err(ErrorInternalServerError("Failed to read response"));
+                }
+            };
+
+            let stored = {
+                let response_body = response_body.to_vec();
+                web::block(move || {
+                    let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+                    diesel::update(idempotency_keys::table.find((user_id, &key)))
+                        .set((
+                            idempotency_keys::status_code.eq(status_code),
+                            idempotency_keys::content_type.eq(content_type),
+                            idempotency_keys::response_body.eq(response_body),
+                        ))
+                        .execute(&mut conn)
+                        .map_err(|_| "Failed to store response")
+                })
+                .await
+            };
+
+            // The request was handled either way, retries will just be refused for a while
+            match stored {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => eprintln!("Idempotency key not stored: {}", e),
+                Err(e) => eprintln!("Idempotency key not stored: {}", e),
+            }
+
+            let res = res.set_body(response_body).map_into_boxed_body();
+            Ok(ServiceResponse::new(req, res))
+        })
+    }
+}
+
+fn body_too_large() -> HttpResponse {
+    HttpResponse::PayloadTooLarge().json(json!({
+        "error": "Request body is too large"
+    }))
+}
+
+// Hashes what makes two requests the same: method, path, query and body
+fn fingerprint(req: &ServiceRequest, body: &[u8]) -> String {
+    let path = req
+        .uri()
+        .path_and_query()
+        .map_or(req.path(), |path| path.as_str());
+
+    let mut hasher = Sha256::new();
+    hasher.update(req.method().as_str());
+    hasher.update(b" ");
+    hasher.update(path);
+    hasher.update(b"\n");
+
+    // Clients pick a new random multipart boundary for every attempt, so it is left out
+    let boundary = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .and_then(|content_type| content_type.split_once("boundary="))
+        .map(|(_, boundary)| {
+            boundary
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .trim_matches('"')
+        })
+        .filter(|boundary| !boundary.is_empty());
+    let mut rest = body;
+    if let Some(boundary) = boundary.map(str::as_bytes) {
+        while let Some(at) = rest
+            .windows(boundary.len())
+            .position(|window| window == boundary)
+        {
+            hasher.update(&rest[..at]);
+            rest = &rest[at + boundary.len()..];
+        }
+    }
+    hasher.update(rest);
+
+    hex::encode(hasher.finalize())
+}
+
+// Claims the key for a request, unless it was already used
+fn claim(
+    pool: &DbPool,
+    user_id: i32,
+    key: &str,
+    fingerprint: &str,
+    ttl_hours: i32,
+) -> Result<Claim, &'static str> {
+    let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+    // Expired keys and abandoned claims can be used again
+    diesel::delete(
+        idempotency_keys::table.find((user_id, key)).filter(
+            idempotency_keys::created_at.lt(now - ttl_hours.hours()).or(
+                idempotency_keys::status_code
+                    .is_null()
+                    .and(idempotency_keys::claimed_at.lt(now - CLAIM_LEASE_MINUTES.minutes())),
+            ),
+        ),
+    )
+    .execute(&mut conn)
+    .map_err(|_| "Failed to expire idempotency key")?;
+
+    let claimed = diesel::insert_into(idempotency_keys::table)
+        .values(&NewIdempotencyKey {
+            user_id,
+            key,
+            fingerprint,
+        })
+        .on_conflict_do_nothing()
+        .execute(&mut conn)
+        .map_err(|_| "Failed to claim idempotency key")?;
+
+    if claimed > 0 {
+        return Ok(Claim::Claimed);
+    }
+
+    let stored = idempotency_keys::table
+        .find((user_id, key))
+        .select((
+            idempotency_keys::fingerprint,
+            idempotency_keys::status_code,
+            idempotency_keys::content_type,
+            idempotency_keys::response_body,
+        ))
+        .first::<StoredRequest>(&mut conn)
+        .optional()
+        .map_err(|_| "Failed to load idempotency key")?;
+
+    // A key released in the meantime is treated as still in use
+    match stored {
+        Some(stored) if stored.fingerprint != fingerprint => Ok(Claim::Mismatch),
+        Some(stored) if stored.status_code.is_some() => Ok(Claim::Replay(stored)),
+        _ => Ok(Claim::InProgress),
+    }
+}
+
+// Refreshes the claim on a key until the returned task is aborted, so that requests
+// that take a while are not handled twice
+fn keep_claimed(pool: web::Data<DbPool>, user_id: i32, key: String) -> rt::task::JoinHandle<()> {
+    rt::spawn(async move {
+        let mut interval = rt::time::interval(CLAIM_REFRESH);
+        // The first tick completes right away, when the claim was just made
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+
+            let pool = pool.clone();
+            let key = key.clone();
+            let result = web::block(move || {
+                let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+                diesel::update(
+                    idempotency_keys::table
+                        .find((user_id, &key))
+                        .filter(idempotency_keys::status_code.is_null()),
+                )
+                .set(idempotency_keys::claimed_at.eq(now))
+                .execute(&mut conn)
+                .map_err(|_| "Failed to refresh idempotency key")
+            })
+            .await;
+
+            match result {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => eprintln!("Idempotency key not refreshed: {}", e),
+                Err(e) => eprintln!("Idempotency key not refreshed: {}", e),
+            }
+        }
+    })
+}
+
+// Gives up a claimed key, so that the request can be retried with it
+async fn release(pool: web::Data<DbPool>, user_id: i32, key: String) {
+    let result = web::block(move || {
+        let mut conn = pool.get().map_err(|_| "Failed to get DB connection")?;
+
+        diesel::delete(idempotency_keys::table.find((user_id, &key)))
+            .execute(&mut conn)
+            .map_err(|_| "Failed to release idempotency key")
+    })
+    .await;
+
+    match result {
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => eprintln!("Idempotency key not released: {}", e),
+        Err(e) => eprintln!("Idempotency key not released: {}", e),
+    }
+}
+
+// Builds the response stored for a request that was already handled
+fn replay(stored: StoredRequest) -> HttpResponse {
+    let status = stored
+        .status_code
+        .and_then(|status_code| u16::try_from(status_code).ok())
+        .and_then(|status_code| StatusCode::from_u16(status_code).ok())
+        .unwrap_or(StatusCode::OK);
+
+    let mut response = HttpResponse::build(status);
+    response.insert_header((IDEMPOTENT_REPLAYED, "true"));
+    if let Some(content_type) = stored.content_type {
+        response.content_type(content_type);
+    }
+    response.body(stored.response_body.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, test};
+    use diesel::PgConnection;
+    use diesel::r2d2::{self, ConnectionManager};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use uuid::Uuid;
+
+    use crate::models::user::NewUser;
+    use crate::schema::users;
+
+    // The middleware keeps its state in the database, so these tests need one and are
+    // ignored by default. Run them with
+    // `DATABASE_URL=postgres://localhost/twitter_test cargo test -- --ignored`
+    fn test_pool() -> DbPool {
+        let database_url =
+            std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for database tests");
+        r2d2::Pool::builder()
+            .max_size(4)
+            .build(ConnectionManager::<PgConnection>::new(database_url))
+            .expect("Failed to create database connection pool")
+    }
+
+    fn create_user(pool: &DbPool) -> i32 {
+        diesel::insert_into(users::table)
+            .values(&NewUser {
+                username: format!("idempotency_{}", Uuid::new_v4().simple()),
+                password_hash: String::new(),
+            })
+            .returning(users::id)
+            .get_result(&mut pool.get().unwrap())
+            .unwrap()
+    }
+
+    fn delete_user(pool: &DbPool, user_id: i32) {
+        let mut conn = pool.get().unwrap();
+        diesel::delete(idempotency_keys::table.filter(idempotency_keys::user_id.eq(user_id)))
+            .execute(&mut conn)
+            .unwrap();
+        diesel::delete(users::table.find(user_id))
+            .execute(&mut conn)
+            .unwrap();
+    }
+
+    // Serves `POST /things`, counting how many times the handler ran. The first
+    // `failures` requests fail with a server error.
+    macro_rules! test_app {
+        ($pool:expr, $user_id:expr, $calls:expr, $failures:expr) => {{
+            let calls = $calls.clone();
+            let user_id = $user_id;
+            test::init_service(
+                App::new()
+                    .app_data(web::Data::new($pool.clone()))
+                    .wrap(IdempotencyMiddleware::new().body_limit(64))
+                    .wrap_fn(move |req, srv| {
+                        req.extensions_mut().insert(AuthedUserId(user_id));
+                        srv.call(req)
+                    })
+                    .route(
+                        "/things",
+                        web::post().to(move |body: web::Bytes| {
+                            let calls = calls.clone();
+                            async move {
+                                let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                                rt::time::sleep(Duration::from_millis(200)).await;
+                                if call <= $failures {
+                                    return HttpResponse::InternalServerError().finish();
+                                }
+                                HttpResponse::Created().json(json!({
+                                    "call": call,
+                                    "body": String::from_utf8_lossy(&body)
+                                }))
+                            }
+                        }),
+                    ),
+            )
+            .await
+        }};
+    }
+
+    fn post(key: &str, body: &'static str) -> test::TestRequest {
+        test::TestRequest::post()
+            .uri("/things")
+            .insert_header((IDEMPOTENCY_KEY, key))
+            .set_payload(body)
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn retries_replay_the_first_response() {
+        let pool = test_pool();
+        let user_id = create_user(&pool);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = test_app!(pool, user_id, calls, 0);
+
+        let first = test::call_service(&app, post("replay", "a").to_request()).await;
+        assert_eq!(first.status(), StatusCode::CREATED);
+        assert!(first.headers().get(IDEMPOTENT_REPLAYED).is_none());
+        let first_body = test::read_body(first).await;
+
+        let retry = test::call_service(&app, post("replay", "a").to_request()).await;
+        assert_eq!(retry.status(), StatusCode::CREATED);
+        assert_eq!(retry.headers().get(IDEMPOTENT_REPLAYED).unwrap(), "true");
+        assert_eq!(
+            retry.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        assert_eq!(test::read_body(retry).await, first_body);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        delete_user(&pool, user_id);
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn reusing_a_key_for_another_request_is_refused() {
+        let pool = test_pool();
+        let user_id = create_user(&pool);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = test_app!(pool, user_id, calls, 0);
+
+        let first = test::call_service(&app, post("mismatch", "a").to_request()).await;
+        assert_eq!(first.status(), StatusCode::CREATED);
+
+        let other = test::call_service(&app, post("mismatch", "b").to_request()).await;
+        assert_eq!(other.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        delete_user(&pool, user_id);
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn concurrent_requests_with_a_key_are_handled_once() {
+        let pool = test_pool();
+        let user_id = create_user(&pool);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = test_app!(pool, user_id, calls, 0);
+
+        let (first, second) = futures_util::join!(
+            test::call_service(&app, post("concurrent", "a").to_request()),
+            async {
+                rt::time::sleep(Duration::from_millis(50)).await;
+                test::call_service(&app, post("concurrent", "a").to_request()).await
+            }
+        );
+        assert_eq!(first.status(), StatusCode::CREATED);
+        assert_eq!(second.status(), StatusCode::CONFLICT);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        delete_user(&pool, user_id);
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn keys_are_released_after_server_errors() {
+        let pool = test_pool();
+        let user_id = create_user(&pool);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = test_app!(pool, user_id, calls, 1);
+
+        let failed = test::call_service(&app, post("release", "a").to_request()).await;
+        assert_eq!(failed.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let retry = test::call_service(&app, post("release", "a").to_request()).await;
+        assert_eq!(retry.status(), StatusCode::CREATED);
+        assert!(retry.headers().get(IDEMPOTENT_REPLAYED).is_none());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        delete_user(&pool, user_id);
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn abandoned_claims_can_be_reclaimed() {
+        let pool = test_pool();
+        let user_id = create_user(&pool);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = test_app!(pool, user_id, calls, 0);
+
+        // A claim left behind by a server that stopped while handling the request
+        diesel::insert_into(idempotency_keys::table)
+            .values((
+                idempotency_keys::user_id.eq(user_id),
+                idempotency_keys::key.eq("abandoned"),
+                idempotency_keys::fingerprint.eq(""),
+                idempotency_keys::claimed_at.eq(now - (CLAIM_LEASE_MINUTES + 1).minutes()),
+            ))
+            .execute(&mut pool.get().unwrap())
+            .unwrap();
+
+        let res = test::call_service(&app, post("abandoned", "a").to_request()).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        delete_user(&pool, user_id);
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn claims_refreshed_within_the_lease_are_kept() {
+        let pool = test_pool();
+        let user_id = create_user(&pool);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = test_app!(pool, user_id, calls, 0);
+
+        // A request claimed long ago that is still being handled, and so refreshed
+        diesel::insert_into(idempotency_keys::table)
+            .values((
+                idempotency_keys::user_id.eq(user_id),
+                idempotency_keys::key.eq("slow"),
+                idempotency_keys::fingerprint.eq(""),
+                idempotency_keys::created_at.eq(now - (CLAIM_LEASE_MINUTES * 10).minutes()),
+            ))
+            .execute(&mut pool.get().unwrap())
+            .unwrap();
+
+        let res = test::call_service(&app, post("slow", "a").to_request()).await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        delete_user(&pool, user_id);
+    }
+
+    #[actix_web::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn bodies_over_the_limit_are_refused() {
+        let pool = test_pool();
+        let user_id = create_user(&pool);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = test_app!(pool, user_id, calls, 0);
+
+        let body = "a".repeat(65);
+        let res = test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri("/things")
+                .insert_header((IDEMPOTENCY_KEY, "large"))
+                .set_payload(body)
+                .to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        delete_user(&pool, user_id);
+    }
+
+    #[actix_web::test]
+    async fn fingerprints_ignore_the_multipart_boundary() {
+        let multipart = |boundary: &str| {
+            let req = test::TestRequest::post()
+                .uri("/media")
+                .insert_header((
+                    header::CONTENT_TYPE,
+                    format!("multipart/form-data; boundary={}", boundary),
+                ))
+                .to_srv_request();
+            let body = format!(
+                "--{0}\r\nContent-Disposition: form-data; name=\"alt_text\"\r\n\r\nA cat\r\n--{0}--\r\n",
+                boundary
+            );
+            fingerprint(&req, body.as_bytes())
+        };
+
+        assert_eq!(multipart("first"), multipart("second"));
+        assert_ne!(
+            fingerprint(&post("key", "a").to_srv_request(), b"a"),
+            fingerprint(&post("key", "b").to_srv_request(), b"b")
+        );
+    }
+}
@@ -1,2 +1,3 @@
 // Export middleware modules
 pub mod auth_middleware;
+pub mod idempotency_middleware;
@@ -0,0 +1,51 @@
+use chrono::NaiveDateTime;
+use diesel::{Associations, Identifiable, Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::user::User;
+use crate::schema::{bookmark_folders, bookmarks};
+
+/// Represents a named folder of bookmarks in the database
+#[derive(Serialize, Deserialize, Queryable, Identifiable, Associations, Debug, ToSchema)]
+#[schema(example = json!({
+    "id": 1,
+    "user_id": 1,
+    "name": "Read later",
+    "created_at": "2025-04-19T07:30:00"
+}))]
+#[diesel(belongs_to(User))]
+#[diesel(table_name = bookmark_folders)]
+pub struct BookmarkFolder {
+    /// Unique identifier for the folder
+    pub id: i32,
+    /// ID of the user who owns the folder
+    pub user_id: i32,
+    /// Name of the folder
+    pub name: String,
+    /// Timestamp when the folder was created
+    #[schema(value_type = String, format = "date-time", example = "2025-04-19T07:30:00")]
+    pub created_at: NaiveDateTime,
+}
+
+/// Used for creating new bookmark folders in the database
+#[derive(Deserialize, Insertable, ToSchema)]
+#[diesel(table_name = bookmark_folders)]
+pub struct NewBookmarkFolder {
+    /// ID of the user creating the folder
+    pub user_id: i32,
+    /// Name of the folder
+    pub name: String,
+}
+
+/// Used for creating new bookmarks in the database
+#[derive(Deserialize, Insertable, ToSchema)]
+#[diesel(table_name = bookmarks)]
+pub struct NewBookmark {
+    /// ID of the user bookmarking the post
+    pub user_id: i32,
+    /// ID of the bookmarked post
+    pub post_id: i32,
+    /// ID of the folder to file the bookmark in, if any
+    pub folder_id: Option<i32>,
+}
@@ -0,0 +1,204 @@
+use chrono::NaiveDateTime;
+use diesel::pg::Pg;
+use diesel::{AsChangeset, Associations, Identifiable, Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::post::{ReplyAudience, Visibility};
+use crate::models::user::User;
+use crate::schema::drafts;
+
+/// Represents a post being written, saved on the server, in the database
+#[derive(Serialize, Deserialize, Identifiable, Associations, Debug, ToSchema)]
+#[schema(example = json!({
+    "id": 1,
+    "user_id": 1,
+    "content": "Half a thought",
+    "quoted_post_id": null,
+    "in_reply_to_id": null,
+    "reply_audience": null,
+    "visibility": null,
+    "content_warning": null,
+    "sensitive": false,
+    "lang": null,
+    "media_ids": [],
+    "poll": null,
+    "version": 3,
+    "created_at": "2025-04-19T07:30:00",
+    "updated_at": "2025-04-19T07:32:10"
+}))]
+#[diesel(belongs_to(User))]
+#[diesel(table_name = drafts)]
+pub struct Draft {
+    /// Unique identifier for the draft
+    pub id: i32,
+    /// ID of the user who owns the draft
+    pub user_id: i32,
+    /// Content written so far
+    pub content: String,
+    /// ID of the post to quote, if any
+    pub quoted_post_id: Option<i32>,
+    /// ID of the post to reply to, if any
+    pub in_reply_to_id: Option<i32>,
+    /// Who can reply to the post, defaulting to everyone
+    pub reply_audience: Option<ReplyAudience>,
+    /// Who can see the post, defaulting to the author's `default_visibility` setting
+    pub visibility: Option<Visibility>,
+    /// Warning shown instead of the content until it is expanded
+    pub content_warning: Option<String>,
+    /// Whether the post is sensitive
+    pub sensitive: bool,
+    /// ISO 639-1 code of the language of the post, detected when published if missing
+    pub lang: Option<String>,
+    /// IDs of uploaded images to attach, in order
+    pub media_ids: Vec<i32>,
+    /// Poll to attach to the post, if any
+    pub poll: Option<DraftPoll>,
+    /// Incremented on every change, to detect changes made from another device
+    pub version: i32,
+    /// Timestamp when the draft was created
+    #[schema(value_type = String, format = "date-time", example = "2025-04-19T07:30:00")]
+    pub created_at: NaiveDateTime,
+    /// Timestamp when the draft was last changed
+    #[schema(value_type = String, format = "date-time", example = "2025-04-19T07:32:10")]
+    pub updated_at: NaiveDateTime,
+}
+
+/// Poll of a draft, checked like the poll of a new post when the draft is published
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+#[schema(example = json!({
+    "options": ["Tabs", "Spaces"],
+    "duration_minutes": 1440
+}))]
+pub struct DraftPoll {
+    /// Options of the poll
+    pub options: Vec<String>,
+    /// How long the poll is open after the post is published
+    pub duration_minutes: i32,
+}
+
+type DraftRow = (
+    i32,
+    i32,
+    String,
+    Option<i32>,
+    i32,
+    NaiveDateTime,
+    NaiveDateTime,
+    Option<i32>,
+    Option<ReplyAudience>,
+    Option<Visibility>,
+    Option<String>,
+    bool,
+    Option<String>,
+    Vec<i32>,
+    Option<Vec<String>>,
+    Option<i32>,
+);
+
+// Written by hand to gather the poll columns into a single field
+impl Queryable<drafts::SqlType, Pg> for Draft {
+    type Row = DraftRow;
+
+    fn build(row: Self::Row) -> diesel::deserialize::Result<Self> {
+        let (
+            id,
+            user_id,
+            content,
+            quoted_post_id,
+            version,
+            created_at,
+            updated_at,
+            in_reply_to_id,
+            reply_audience,
+            visibility,
+            content_warning,
+            sensitive,
+            lang,
+            media_ids,
+            poll_options,
+            poll_duration_minutes,
+        ) = row;
+
+        Ok(Draft {
+            id,
+            user_id,
+            content,
+            quoted_post_id,
+            in_reply_to_id,
+            reply_audience,
+            visibility,
+            content_warning,
+            sensitive,
+            lang,
+            media_ids,
+            poll: poll_options
+                .zip(poll_duration_minutes)
+                .map(|(options, duration_minutes)| DraftPoll {
+                    options,
+                    duration_minutes,
+                }),
+            version,
+            created_at,
+            updated_at,
+        })
+    }
+}
+
+/// Used for creating new drafts in the database
+#[derive(Deserialize, Insertable, ToSchema)]
+#[diesel(table_name = drafts)]
+pub struct NewDraft {
+    /// ID of the user creating the draft
+    pub user_id: i32,
+    /// Content written so far
+    pub content: String,
+    /// ID of the post to quote, if any
+    pub quoted_post_id: Option<i32>,
+    /// ID of the post to reply to, if any
+    pub in_reply_to_id: Option<i32>,
+    /// Who can reply to the post
+    pub reply_audience: Option<ReplyAudience>,
+    /// Who can see the post
+    pub visibility: Option<Visibility>,
+    /// Warning shown instead of the content
+    pub content_warning: Option<String>,
+    /// Whether the post is sensitive
+    pub sensitive: bool,
+    /// Language of the post
+    pub lang: Option<String>,
+    /// IDs of uploaded images to attach
+    pub media_ids: Vec<i32>,
+    /// Options of the poll, if any
+    pub poll_options: Option<Vec<String>>,
+    /// Duration of the poll, if any
+    pub poll_duration_minutes: Option<i32>,
+}
+
+/// Used for changing drafts in the database, leaving out fields that are `None`
+#[derive(AsChangeset)]
+#[diesel(table_name = drafts)]
+pub struct DraftChanges {
+    /// New content, if it changed
+    pub content: Option<String>,
+    /// New quoted post, if it changed, where `Some(None)` removes it
+    pub quoted_post_id: Option<Option<i32>>,
+    /// New replied post, if it changed, where `Some(None)` removes it
+    pub in_reply_to_id: Option<Option<i32>>,
+    /// New reply audience, if it changed, where `Some(None)` resets it
+    pub reply_audience: Option<Option<ReplyAudience>>,
+    /// New visibility, if it changed, where `Some(None)` resets it
+    pub visibility: Option<Option<Visibility>>,
+    /// New content warning, if it changed, where `Some(None)` removes it
+    pub content_warning: Option<Option<String>>,
+    /// Whether the post is sensitive, if it changed
+    pub sensitive: Option<bool>,
+    /// New language, if it changed, where `Some(None)` resets it
+    pub lang: Option<Option<String>>,
+    /// New media, if it changed
+    pub media_ids: Option<Vec<i32>>,
+    /// New poll options, if the poll changed, where `Some(None)` removes the poll
+    pub poll_options: Option<Option<Vec<String>>>,
+    /// New poll duration, if the poll changed, where `Some(None)` removes the poll
+    pub poll_duration_minutes: Option<Option<i32>>,
+}
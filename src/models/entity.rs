@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::models::post::Post;
+use crate::schema::{post_mentions, users};
+use crate::util::entities::{self, EntityKind};
+
+/// Type of a rich-text entity
+#[derive(Serialize, Clone, Copy, Debug, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityType {
+    Url,
+    Hashtag,
+    Mention,
+}
+
+/// A link, hashtag or mention in the content of a post
+///
+/// `start` and `end` count UTF-16 code units (as used by JavaScript, Swift and
+/// Java strings), `byte_start` and `byte_end` count bytes of the UTF-8 content.
+/// Ranges are half-open and include the leading `#` or `@`.
+#[derive(Serialize, Clone, Debug, ToSchema)]
+#[schema(example = json!({
+    "type": "mention",
+    "start": 6,
+    "end": 14,
+    "byte_start": 6,
+    "byte_end": 14,
+    "user_id": 1,
+    "username": "johndoe"
+}))]
+pub struct Entity {
+    /// Type of the entity
+    #[serde(rename = "type")]
+    pub entity_type: EntityType,
+    /// Start of the entity, in UTF-16 code units
+    pub start: usize,
+    /// End of the entity (exclusive), in UTF-16 code units
+    pub end: usize,
+    /// Start of the entity, in bytes
+    pub byte_start: usize,
+    /// End of the entity (exclusive), in bytes
+    pub byte_end: usize,
+    /// Full URL the link points to, for URLs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Normalized hashtag, without the leading `#`, for hashtags
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hashtag: Option<String>,
+    /// ID of the mentioned user, for mentions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<i32>,
+    /// Username of the mentioned user, for mentions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+}
+
+impl Entity {
+    /// Extracts the entities of each post, keyed by post ID
+    ///
+    /// Mentions are resolved against the users linked to the post, so mentions of
+    /// usernames that do not exist are left out.
+    pub fn load_for_posts(
+        conn: &mut PgConnection,
+        posts: &[Post],
+    ) -> QueryResult<HashMap<i32, Vec<Entity>>> {
+        let post_ids: Vec<i32> = posts.iter().map(|post| post.id).collect();
+
+        let mut mentioned_users: HashMap<(i32, String), (i32, String)> = HashMap::new();
+        for (post_id, user_id, username) in post_mentions::table
+            .inner_join(users::table)
+            .filter(post_mentions::post_id.eq_any(&post_ids))
+            .select((post_mentions::post_id, users::id, users::username))
+            .load::<(i32, i32, String)>(conn)?
+        {
+            mentioned_users.insert((post_id, username.to_lowercase()), (user_id, username));
+        }
+
+        Ok(posts
+            .iter()
+            .map(|post| {
+                let post_entities = Entity::parse(&post.content, |username| {
+                    mentioned_users
+                        .get(&(post.id, username.to_string()))
+                        .cloned()
+                });
+                (post.id, post_entities)
+            })
+            .collect())
+    }
+
+    // Extracts the entities of `text`, looking up the ID and username of mentioned
+    // users by lowercase username
+    fn parse(text: &str, mentioned_user: impl Fn(&str) -> Option<(i32, String)>) -> Vec<Entity> {
+        entities::parse(text)
+            .into_iter()
+            .filter_map(|(kind, range)| {
+                let matched = &text[range.clone()];
+                let (entity_type, url, hashtag, mentioned) = match kind {
+                    EntityKind::Url => (
+                        EntityType::Url,
+                        Some(entities::expand_url(matched)),
+                        None,
+                        None,
+                    ),
+                    EntityKind::Hashtag => (
+                        EntityType::Hashtag,
+                        None,
+                        Some(entities::normalize_hashtag(matched)),
+                        None,
+                    ),
+                    EntityKind::Mention => {
+                        let username = matched.trim_start_matches(['@', '＠']).to_lowercase();
+                        let user = mentioned_user(&username)?;
+                        (EntityType::Mention, None, None, Some(user))
+                    }
+                };
+                let (user_id, username) = mentioned.unzip();
+
+                Some(Entity {
+                    entity_type,
+                    start: entities::utf16_offset(text, range.start),
+                    end: entities::utf16_offset(text, range.end),
+                    byte_start: range.start,
+                    byte_end: range.end,
+                    url,
+                    hashtag,
+                    user_id,
+                    username,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_count_utf16_code_units_after_astral_emoji() {
+        // 🦀 is 4 bytes in UTF-8 and a surrogate pair in UTF-16
+        let text = "🦀 @ferris likes #rust 🦀 https://rust-lang.org";
+        let entities = Entity::parse(text, |username| {
+            (username == "ferris").then(|| (7, "Ferris".to_string()))
+        });
+
+        let offsets: Vec<_> = entities
+            .iter()
+            .map(|entity| (entity.start, entity.end, entity.byte_start, entity.byte_end))
+            .collect();
+        assert_eq!(
+            offsets,
+            [(3, 10, 5, 12), (17, 22, 19, 24), (26, 47, 30, 51)]
+        );
+
+        assert!(matches!(entities[0].entity_type, EntityType::Mention));
+        assert_eq!(entities[0].user_id, Some(7));
+        assert_eq!(entities[0].username.as_deref(), Some("Ferris"));
+        assert_eq!(entities[1].hashtag.as_deref(), Some("rust"));
+        assert_eq!(entities[2].url.as_deref(), Some("https://rust-lang.org"));
+    }
+
+    #[test]
+    fn mentions_of_unknown_users_are_left_out() {
+        let entities = Entity::parse("👨‍👩‍👧 @nobody #tag", |_| None);
+
+        assert_eq!(entities.len(), 1);
+        assert!(matches!(entities[0].entity_type, EntityType::Hashtag));
+        // The family emoji is 5 code points, 3 of them astral
+        assert_eq!((entities[0].start, entities[0].end), (17, 21));
+    }
+}
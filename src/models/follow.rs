@@ -0,0 +1,15 @@
+use diesel::Insertable;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::schema::follows;
+
+/// Used for creating new follows in the database
+#[derive(Deserialize, Insertable, ToSchema)]
+#[diesel(table_name = follows)]
+pub struct NewFollow {
+    /// ID of the user following
+    pub follower_id: i32,
+    /// ID of the followed user
+    pub followee_id: i32,
+}
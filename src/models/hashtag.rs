@@ -0,0 +1,81 @@
+use chrono::NaiveDateTime;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::{Identifiable, Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::schema::{hashtags, post_hashtags};
+use crate::util::entities;
+
+/// Represents a hashtag in the database
+#[derive(Serialize, Deserialize, Queryable, Identifiable, Debug, ToSchema)]
+#[diesel(table_name = hashtags)]
+pub struct Hashtag {
+    /// Unique identifier for the hashtag
+    pub id: i32,
+    /// Normalized name of the hashtag, without the leading `#`
+    pub name: String,
+    /// Timestamp when the hashtag was first used
+    #[schema(value_type = String, format = "date-time", example = "2025-04-19T07:30:00")]
+    pub created_at: NaiveDateTime,
+}
+
+/// Used for creating new hashtags in the database
+#[derive(Insertable)]
+#[diesel(table_name = hashtags)]
+pub struct NewHashtag<'a> {
+    /// Normalized name of the hashtag
+    pub name: &'a str,
+}
+
+/// Used for linking posts to their hashtags in the database
+#[derive(Insertable)]
+#[diesel(table_name = post_hashtags)]
+pub struct NewPostHashtag {
+    /// ID of the post containing the hashtag
+    pub post_id: i32,
+    /// ID of the hashtag
+    pub hashtag_id: i32,
+}
+
+impl Hashtag {
+    /// Replaces the hashtags indexed for a post with the ones in its content
+    pub fn sync_post(conn: &mut PgConnection, post_id: i32, content: &str) -> QueryResult<()> {
+        diesel::delete(post_hashtags::table.filter(post_hashtags::post_id.eq(post_id)))
+            .execute(conn)?;
+
+        let names = entities::hashtags(content);
+        if names.is_empty() {
+            return Ok(());
+        }
+
+        let new_hashtags = names
+            .iter()
+            .map(|name| NewHashtag { name })
+            .collect::<Vec<_>>();
+        diesel::insert_into(hashtags::table)
+            .values(&new_hashtags)
+            .on_conflict(hashtags::name)
+            .do_nothing()
+            .execute(conn)?;
+
+        let hashtag_ids = hashtags::table
+            .filter(hashtags::name.eq_any(&names))
+            .select(hashtags::id)
+            .load::<i32>(conn)?;
+
+        let links = hashtag_ids
+            .into_iter()
+            .map(|hashtag_id| NewPostHashtag {
+                post_id,
+                hashtag_id,
+            })
+            .collect::<Vec<_>>();
+        diesel::insert_into(post_hashtags::table)
+            .values(&links)
+            .execute(conn)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,28 @@
+use diesel::{Insertable, Queryable};
+
+use crate::schema::idempotency_keys;
+
+/// Used for claiming an idempotency key before handling a request in the database
+#[derive(Insertable)]
+#[diesel(table_name = idempotency_keys)]
+pub struct NewIdempotencyKey<'a> {
+    /// ID of the user who sent the request
+    pub user_id: i32,
+    /// Key sent in the `Idempotency-Key` header
+    pub key: &'a str,
+    /// Hash of the method, path and body of the request
+    pub fingerprint: &'a str,
+}
+
+/// Request first sent with an idempotency key, and its response once handled
+#[derive(Queryable, Debug)]
+pub struct StoredRequest {
+    /// Hash of the method, path and body of the request
+    pub fingerprint: String,
+    /// Status code of the response, missing while the request is being handled
+    pub status_code: Option<i32>,
+    /// Content type of the response, if it had one
+    pub content_type: Option<String>,
+    /// Body of the response
+    pub response_body: Option<Vec<u8>>,
+}
@@ -0,0 +1,15 @@
+use diesel::Insertable;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::schema::likes;
+
+/// Used for creating new likes in the database
+#[derive(Deserialize, Insertable, ToSchema)]
+#[diesel(table_name = likes)]
+pub struct NewLike {
+    /// ID of the user liking the post
+    pub user_id: i32,
+    /// ID of the liked post
+    pub post_id: i32,
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::{Insertable, Queryable};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::schema::{link_cards, post_cards};
+use crate::util::entities;
+
+// Longer URLs get no card
+const MAX_URL_LENGTH: usize = 2048;
+
+/// Represents the cached preview of a URL in the database, without its bookkeeping
+#[derive(Queryable, Debug)]
+pub struct LinkCard {
+    /// URL the card is for
+    pub url: String,
+    /// `summary` or `summary_large_image`
+    pub card_type: Option<String>,
+    /// Title of the page, if it has a preview
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub site_name: Option<String>,
+}
+
+/// Used for creating new link cards in the database, to be fetched later
+#[derive(Insertable)]
+#[diesel(table_name = link_cards)]
+pub struct NewLinkCard<'a> {
+    pub url: &'a str,
+}
+
+/// Used for linking posts to the card of their first URL in the database
+#[derive(Insertable)]
+#[diesel(table_name = post_cards)]
+pub struct NewPostCard {
+    pub post_id: i32,
+    pub link_card_id: i32,
+}
+
+/// Preview of a link as returned by the API
+#[derive(Serialize, Clone, Debug, ToSchema)]
+#[schema(example = json!({
+    "url": "https://www.rust-lang.org",
+    "type": "summary_large_image",
+    "title": "Rust Programming Language",
+    "description": "A language empowering everyone to build reliable and efficient software.",
+    "image_url": "https://www.rust-lang.org/static/images/rust-social-wide.jpg",
+    "site_name": "Rust"
+}))]
+pub struct LinkCardView {
+    /// URL the card is for, as found in the post
+    pub url: String,
+    /// `summary` for a small image or `summary_large_image` for a wide one, as in
+    /// Twitter Cards
+    #[serde(rename = "type")]
+    pub card_type: String,
+    /// Title of the page
+    pub title: String,
+    /// Description of the page, if it has one
+    pub description: Option<String>,
+    /// URL of the preview image, if the page has one
+    pub image_url: Option<String>,
+    /// Name of the site, if the page gives one
+    pub site_name: Option<String>,
+}
+
+impl LinkCardView {
+    /// Builds the views of the cards of each post that has one, keyed by post ID
+    ///
+    /// Posts whose card has not been fetched yet, or whose page has no preview, have
+    /// none.
+    pub fn load_for_posts(
+        conn: &mut PgConnection,
+        post_ids: &[i32],
+    ) -> QueryResult<HashMap<i32, LinkCardView>> {
+        let cards = post_cards::table
+            .inner_join(link_cards::table)
+            .filter(post_cards::post_id.eq_any(post_ids))
+            .filter(link_cards::title.is_not_null())
+            .select((
+                post_cards::post_id,
+                (
+                    link_cards::url,
+                    link_cards::card_type,
+                    link_cards::title,
+                    link_cards::description,
+                    link_cards::image_url,
+                    link_cards::site_name,
+                ),
+            ))
+            .load::<(i32, LinkCard)>(conn)?;
+
+        Ok(cards
+            .into_iter()
+            .filter_map(|(post_id, card)| {
+                let view = LinkCardView {
+                    url: card.url,
+                    card_type: card.card_type.unwrap_or_else(|| "summary".to_string()),
+                    title: card.title?,
+                    description: card.description,
+                    image_url: card.image_url,
+                    site_name: card.site_name,
+                };
+                Some((post_id, view))
+            })
+            .collect())
+    }
+}
+
+/// Links a post to the card of the first URL in its content, replacing any card it
+/// had
+///
+/// Cards are cached per URL: a URL posted before reuses its card, and a new one gets
+/// a card for the link cards job to fetch.
+pub fn sync_post(conn: &mut PgConnection, post_id: i32, content: &str) -> QueryResult<()> {
+    diesel::delete(post_cards::table.filter(post_cards::post_id.eq(post_id))).execute(conn)?;
+
+    let url = entities::url_ranges(content)
+        .into_iter()
+        .map(|range| entities::expand_url(&content[range]))
+        .next()
+        .filter(|url| url.len() <= MAX_URL_LENGTH);
+    let Some(url) = url else {
+        return Ok(());
+    };
+
+    diesel::insert_into(link_cards::table)
+        .values(&NewLinkCard { url: &url })
+        .on_conflict(link_cards::url)
+        .do_nothing()
+        .execute(conn)?;
+    let link_card_id = link_cards::table
+        .filter(link_cards::url.eq(&url))
+        .select(link_cards::id)
+        .first::<i32>(conn)?;
+
+    diesel::insert_into(post_cards::table)
+        .values(&NewPostCard {
+            post_id,
+            link_card_id,
+        })
+        .execute(conn)?;
+
+    Ok(())
+}
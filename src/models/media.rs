@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::{Insertable, Queryable};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::schema::{media, media_variants};
+use crate::storage;
+
+/// Represents an uploaded image in the database
+#[derive(Serialize, Queryable, Debug)]
+pub struct Media {
+    /// Unique identifier for the media
+    pub id: i32,
+    /// ID of the user who uploaded the media
+    pub user_id: i32,
+    /// ID of the post the media is attached to, once it is
+    pub post_id: Option<i32>,
+    /// Position of the media among the attachments of the post
+    pub position: Option<i32>,
+    /// MIME type of the original file
+    pub content_type: String,
+    pub width: i32,
+    pub height: i32,
+    /// Size of the original file
+    pub size_bytes: i32,
+    /// Key of the original file in the media store
+    pub storage_key: String,
+    /// Timestamp when the media was uploaded
+    pub created_at: NaiveDateTime,
+    /// BlurHash placeholder, missing for media uploaded before they were computed
+    pub blurhash: Option<String>,
+    /// Most common colour as `#rrggbb`, missing for media uploaded before it was computed
+    pub dominant_color: Option<String>,
+    /// Description of the image for people who cannot see it
+    pub alt_text: Option<String>,
+}
+
+/// Used for creating new media in the database
+#[derive(Insertable)]
+#[diesel(table_name = media)]
+pub struct NewMedia {
+    pub user_id: i32,
+    pub content_type: String,
+    pub width: i32,
+    pub height: i32,
+    pub size_bytes: i32,
+    pub storage_key: String,
+    pub blurhash: String,
+    pub dominant_color: String,
+    pub alt_text: Option<String>,
+}
+
+/// Represents a resized copy of an image in the database
+#[derive(Queryable, Insertable, Debug)]
+#[diesel(table_name = media_variants)]
+pub struct MediaVariant {
+    /// ID of the original media
+    pub media_id: i32,
+    /// Name of the variant, e.g. `small`
+    pub name: String,
+    pub width: i32,
+    pub height: i32,
+    /// Key of the file in the media store
+    pub storage_key: String,
+}
+
+/// Represents an image as returned by the API
+#[derive(Serialize, Clone, Debug, ToSchema)]
+#[schema(example = json!({
+    "id": 1,
+    "content_type": "image/jpeg",
+    "width": 1600,
+    "height": 1200,
+    "size_bytes": 482133,
+    "alt_text": "A cat asleep on a keyboard",
+    "blurhash": "LEHV6nWB2yk8pyo0adR*.7kCMdnj",
+    "dominant_color": "#4a6f8c",
+    "url": "/media/files/media/5c0e6f0e-3a52-4f0e-9f43-6f1f2a2b8f4e/original.jpg",
+    "variants": [{
+        "name": "thumb",
+        "width": 150,
+        "height": 113,
+        "url": "/media/files/media/5c0e6f0e-3a52-4f0e-9f43-6f1f2a2b8f4e/thumb.jpg"
+    }]
+}))]
+pub struct MediaView {
+    /// Unique identifier for the media, used to attach it to a post
+    pub id: i32,
+    /// MIME type of the original file
+    pub content_type: String,
+    /// Width of the original image, in pixels
+    pub width: i32,
+    /// Height of the original image, in pixels
+    pub height: i32,
+    /// Size of the original file, in bytes
+    pub size_bytes: i32,
+    /// Description of the image for people who cannot see it, if the uploader wrote one
+    pub alt_text: Option<String>,
+    /// BlurHash of the image, to show while it loads
+    pub blurhash: Option<String>,
+    /// Most common colour of the image as `#rrggbb`, to show while it loads
+    pub dominant_color: Option<String>,
+    /// URL of the original file
+    pub url: String,
+    /// Resized copies, smallest first. Only sizes smaller than the original exist.
+    pub variants: Vec<MediaVariantView>,
+}
+
+/// Resized copy of an image as returned by the API
+#[derive(Serialize, Clone, Debug, ToSchema)]
+pub struct MediaVariantView {
+    /// Name of the variant: `thumb`, `small`, `medium` or `large`
+    pub name: String,
+    /// Width of the copy, in pixels
+    pub width: i32,
+    /// Height of the copy, in pixels
+    pub height: i32,
+    /// URL of the copy
+    pub url: String,
+}
+
+impl Media {
+    /// Finds the storage keys of the media attached to the given posts and of their
+    /// variants, so the files can be removed along with the posts
+    pub fn storage_keys_for_posts(
+        conn: &mut PgConnection,
+        post_ids: &[i32],
+    ) -> QueryResult<Vec<String>> {
+        let mut keys = media::table
+            .filter(media::post_id.eq_any(post_ids))
+            .select(media::storage_key)
+            .load::<String>(conn)?;
+        keys.extend(
+            media_variants::table
+                .inner_join(media::table)
+                .filter(media::post_id.eq_any(post_ids))
+                .select(media_variants::storage_key)
+                .load::<String>(conn)?,
+        );
+        Ok(keys)
+    }
+}
+
+impl MediaView {
+    /// Builds the view of an image from its database rows
+    pub fn new(media: Media, variants: Vec<MediaVariant>) -> Self {
+        let mut variants = variants;
+        variants.sort_by_key(|variant| variant.width.max(variant.height));
+
+        MediaView {
+            id: media.id,
+            url: storage::public_url(&media.storage_key),
+            content_type: media.content_type,
+            width: media.width,
+            height: media.height,
+            size_bytes: media.size_bytes,
+            alt_text: media.alt_text,
+            blurhash: media.blurhash,
+            dominant_color: media.dominant_color,
+            variants: variants
+                .into_iter()
+                .map(|variant| MediaVariantView {
+                    url: storage::public_url(&variant.storage_key),
+                    name: variant.name,
+                    width: variant.width,
+                    height: variant.height,
+                })
+                .collect(),
+        }
+    }
+
+    /// Builds the views of the media attached to each post, in order, keyed by post ID
+    pub fn load_for_posts(
+        conn: &mut PgConnection,
+        post_ids: &[i32],
+    ) -> QueryResult<HashMap<i32, Vec<MediaView>>> {
+        let attached = media::table
+            .filter(media::post_id.eq_any(post_ids))
+            .order((media::post_id, media::position))
+            .load::<Media>(conn)?;
+        if attached.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let media_ids: Vec<i32> = attached.iter().map(|media| media.id).collect();
+        let mut variants: HashMap<i32, Vec<MediaVariant>> = HashMap::new();
+        for variant in media_variants::table
+            .filter(media_variants::media_id.eq_any(&media_ids))
+            .load::<MediaVariant>(conn)?
+        {
+            variants.entry(variant.media_id).or_default().push(variant);
+        }
+
+        let mut views: HashMap<i32, Vec<MediaView>> = HashMap::new();
+        for media in attached {
+            let Some(post_id) = media.post_id else {
+                continue;
+            };
+            let media_variants = variants.remove(&media.id).unwrap_or_default();
+            views
+                .entry(post_id)
+                .or_default()
+                .push(MediaView::new(media, media_variants));
+        }
+
+        Ok(views)
+    }
+}
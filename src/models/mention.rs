@@ -0,0 +1,50 @@
+use diesel::Insertable;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+
+use crate::schema::{post_mentions, users};
+use crate::util::entities;
+
+define_sql_function!(fn lower(x: diesel::sql_types::Text) -> diesel::sql_types::Text);
+
+/// Used for linking posts to the users they mention in the database
+#[derive(Insertable)]
+#[diesel(table_name = post_mentions)]
+pub struct NewPostMention {
+    /// ID of the post containing the mention
+    pub post_id: i32,
+    /// ID of the mentioned user
+    pub user_id: i32,
+}
+
+/// Replaces the mentions recorded for a post with the ones in its content
+///
+/// Mentions of usernames that do not exist are ignored.
+pub fn sync_post(conn: &mut PgConnection, post_id: i32, content: &str) -> QueryResult<()> {
+    diesel::delete(post_mentions::table.filter(post_mentions::post_id.eq(post_id)))
+        .execute(conn)?;
+
+    let usernames = entities::mentions(content)
+        .into_iter()
+        .map(|username| username.to_lowercase())
+        .collect::<Vec<_>>();
+    if usernames.is_empty() {
+        return Ok(());
+    }
+
+    let mentioned_ids = users::table
+        .filter(lower(users::username).eq_any(&usernames))
+        .select(users::id)
+        .load::<i32>(conn)?;
+
+    let mentions = mentioned_ids
+        .into_iter()
+        .map(|user_id| NewPostMention { post_id, user_id })
+        .collect::<Vec<_>>();
+    diesel::insert_into(post_mentions::table)
+        .values(&mentions)
+        .on_conflict_do_nothing()
+        .execute(conn)?;
+
+    Ok(())
+}
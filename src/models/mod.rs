@@ -1,3 +1,21 @@
 // Export models
+pub mod bookmark;
+pub mod draft;
+pub mod entity;
+pub mod follow;
+pub mod hashtag;
+pub mod idempotency_key;
+pub mod like;
+pub mod link_card;
+pub mod media;
+pub mod pinned_post;
+pub mod poll;
+pub mod mention;
 pub mod post;
+pub mod post_revision;
+pub mod post_view;
+pub mod repost;
+pub mod trend;
 pub mod user;
+pub mod user_settings;
+pub mod view_count;
@@ -0,0 +1,36 @@
+use diesel::Insertable;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+
+use crate::models::post::Post;
+use crate::schema::{pinned_posts, posts};
+
+/// Used for pinning posts to their author's profile in the database
+#[derive(Insertable)]
+#[diesel(table_name = pinned_posts)]
+pub struct NewPinnedPost {
+    /// ID of the pinned post
+    pub post_id: i32,
+    /// ID of the author of the post
+    pub user_id: i32,
+    /// Where the post is shown among the author's pinned posts, lowest first
+    pub position: i32,
+}
+
+/// Loads the posts a user pinned that `viewer_id` can see, in order
+pub fn load_posts(conn: &mut PgConnection, user_id: i32, viewer_id: i32) -> QueryResult<Vec<Post>> {
+    pinned_posts::table
+        .inner_join(posts::table)
+        .filter(pinned_posts::user_id.eq(user_id))
+        .filter(Post::is_live())
+        .filter(Post::is_visible_to(viewer_id))
+        .order(pinned_posts::position.asc())
+        .select(posts::all_columns)
+        .load::<Post>(conn)
+}
+
+/// Unpins a post, if it is pinned
+pub fn unpin(conn: &mut PgConnection, post_id: i32) -> QueryResult<()> {
+    diesel::delete(pinned_posts::table.filter(pinned_posts::post_id.eq(post_id))).execute(conn)?;
+    Ok(())
+}
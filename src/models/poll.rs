@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use diesel::dsl::{count_star, now};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::{Insertable, Queryable};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::models::post::Post;
+use crate::schema::{poll_options, poll_votes, polls};
+
+/// Represents the poll attached to a post in the database
+#[derive(Queryable, Debug)]
+pub struct Poll {
+    /// ID of the post carrying the poll
+    pub post_id: i32,
+    /// How long the poll is open after the post is published, in minutes
+    pub duration_minutes: i32,
+}
+
+impl Poll {
+    /// Timestamp when the poll closes, given the post carrying it
+    pub fn closes_at(&self, post: &Post) -> NaiveDateTime {
+        post.created_at + chrono::Duration::minutes(self.duration_minutes.into())
+    }
+}
+
+/// Used for creating new polls in the database
+#[derive(Insertable)]
+#[diesel(table_name = polls)]
+pub struct NewPoll {
+    /// ID of the post carrying the poll
+    pub post_id: i32,
+    /// How long the poll is open after the post is published, in minutes
+    pub duration_minutes: i32,
+}
+
+/// Represents an option of a poll in the database, without its position
+#[derive(Queryable, Debug)]
+pub struct PollOption {
+    /// Unique identifier for the option
+    pub id: i32,
+    /// ID of the post carrying the poll
+    pub post_id: i32,
+    /// Text of the option
+    pub label: String,
+}
+
+/// Used for creating new poll options in the database
+#[derive(Insertable)]
+#[diesel(table_name = poll_options)]
+pub struct NewPollOption {
+    /// ID of the post carrying the poll
+    pub post_id: i32,
+    /// Position of the option in the poll, starting at 0
+    pub position: i32,
+    /// Text of the option
+    pub label: String,
+}
+
+/// Used for recording votes in the database
+#[derive(Insertable)]
+#[diesel(table_name = poll_votes)]
+pub struct NewPollVote {
+    /// ID of the post carrying the poll
+    pub post_id: i32,
+    /// ID of the user voting
+    pub user_id: i32,
+    /// ID of the chosen option
+    pub option_id: i32,
+}
+
+/// Represents a poll as returned by the API
+///
+/// Vote counts are only included once the requesting user has voted, when the poll
+/// is closed, or for the author of the post.
+#[derive(Serialize, Clone, Debug, ToSchema)]
+#[schema(example = json!({
+    "options": [
+        { "id": 1, "label": "Tabs", "votes": 12 },
+        { "id": 2, "label": "Spaces", "votes": 30 }
+    ],
+    "closes_at": "2025-04-20T07:30:00",
+    "closed": false,
+    "total_votes": 42,
+    "my_vote": 2
+}))]
+pub struct PollView {
+    /// Options in the order they were given
+    pub options: Vec<PollOptionView>,
+    /// Timestamp when the poll closes
+    #[schema(value_type = String, format = "date-time", example = "2025-04-20T07:30:00")]
+    pub closes_at: NaiveDateTime,
+    /// Whether voting is over
+    pub closed: bool,
+    /// Total number of votes, if results are visible
+    pub total_votes: Option<i64>,
+    /// ID of the option the requesting user voted for, if any
+    pub my_vote: Option<i32>,
+}
+
+/// Option of a poll as returned by the API
+#[derive(Serialize, Clone, Debug, ToSchema)]
+pub struct PollOptionView {
+    /// Unique identifier for the option, used to vote
+    pub id: i32,
+    /// Text of the option
+    pub label: String,
+    /// Number of votes for the option, if results are visible
+    pub votes: Option<i64>,
+}
+
+impl PollView {
+    /// Builds the views of the polls attached to `posts` as seen by `viewer_id`, keyed
+    /// by post ID
+    pub fn load_for_posts(
+        conn: &mut PgConnection,
+        viewer_id: i32,
+        posts: &[Post],
+    ) -> QueryResult<HashMap<i32, PollView>> {
+        let post_ids: Vec<i32> = posts.iter().map(|post| post.id).collect();
+
+        let polls: HashMap<i32, Poll> = polls::table
+            .filter(polls::post_id.eq_any(&post_ids))
+            .load::<Poll>(conn)?
+            .into_iter()
+            .map(|poll| (poll.post_id, poll))
+            .collect();
+        if polls.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let poll_ids: Vec<i32> = polls.keys().copied().collect();
+
+        let mut options: HashMap<i32, Vec<PollOption>> = HashMap::new();
+        for option in poll_options::table
+            .filter(poll_options::post_id.eq_any(&poll_ids))
+            .order(poll_options::position.asc())
+            .select((poll_options::id, poll_options::post_id, poll_options::label))
+            .load::<PollOption>(conn)?
+        {
+            options.entry(option.post_id).or_default().push(option);
+        }
+
+        let vote_counts: HashMap<i32, i64> = poll_votes::table
+            .filter(poll_votes::post_id.eq_any(&poll_ids))
+            .group_by(poll_votes::option_id)
+            .select((poll_votes::option_id, count_star()))
+            .load::<(i32, i64)>(conn)?
+            .into_iter()
+            .collect();
+
+        let viewer_votes: HashMap<i32, i32> = poll_votes::table
+            .filter(poll_votes::post_id.eq_any(&poll_ids))
+            .filter(poll_votes::user_id.eq(viewer_id))
+            .select((poll_votes::post_id, poll_votes::option_id))
+            .load::<(i32, i32)>(conn)?
+            .into_iter()
+            .collect();
+
+        // Use the database clock, which also set posts.created_at
+        let current_time = diesel::select(now).get_result::<NaiveDateTime>(conn)?;
+
+        Ok(posts
+            .iter()
+            .filter_map(|post| {
+                let poll = polls.get(&post.id)?;
+                let closes_at = poll.closes_at(post);
+                let closed = closes_at <= current_time;
+                let my_vote = viewer_votes.get(&post.id).copied();
+                let show_results = closed || my_vote.is_some() || post.user_id == viewer_id;
+
+                let options = options
+                    .get(&post.id)
+                    .map(|options| {
+                        options
+                            .iter()
+                            .map(|option| PollOptionView {
+                                id: option.id,
+                                label: option.label.clone(),
+                                votes: show_results
+                                    .then(|| vote_counts.get(&option.id).copied().unwrap_or(0)),
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                let total_votes =
+                    show_results.then(|| options.iter().filter_map(|option| option.votes).sum());
+
+                Some((
+                    post.id,
+                    PollView {
+                        options,
+                        closes_at,
+                        closed,
+                        total_votes,
+                        my_vote,
+                    },
+                ))
+            })
+            .collect())
+    }
+}
@@ -1,10 +1,19 @@
+use std::io::Write;
+
 use chrono::NaiveDateTime;
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::dsl::{And, IsNull};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgConnection, PgValue};
+use diesel::prelude::*;
+use diesel::serialize::{self, IsNull as SqlIsNull, Output, ToSql};
+use diesel::sql_types::{Bool, Nullable, Text};
 use diesel::{Associations, Identifiable, Insertable, Queryable};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
-use crate::models::user::User;
-use crate::schema::posts;
+use crate::models::{hashtag::Hashtag, link_card, mention, user::User};
+use crate::schema::{follows, post_mentions, posts};
 
 /// Represents a tweet post with user information and content in the database
 #[derive(Serialize, Deserialize, Queryable, Identifiable, Associations, Debug, ToSchema)]
@@ -12,7 +21,22 @@ use crate::schema::posts;
     "id": 1,
     "user_id": 1,
     "content": "Hello world from Rust!", 
-    "created_at": "2025-04-19T07:30:00"
+    "created_at": "2025-04-19T07:30:00",
+    "quoted_post_id": null,
+    "edited_at": null,
+    "edit_count": 0,
+    "deleted_at": null,
+    "deleted_by": null,
+    "publish_at": null,
+    "visibility": "public",
+    "in_reply_to_id": null,
+    "conversation_id": 1,
+    "reply_audience": "everyone",
+    "content_warning": null,
+    "sensitive": false,
+    "sensitive_by_moderator": false,
+    "lang": "en",
+    "lang_overridden": false
 }))]
 #[diesel(belongs_to(User))]
 #[diesel(table_name = posts)]
@@ -26,6 +50,257 @@ pub struct Post {
     /// Timestamp when the post was created
     #[schema(value_type = String, format = "date-time", example = "2025-04-19T07:30:00")]
     pub created_at: NaiveDateTime,
+    /// ID of the post this post quotes, if it is a quote post
+    pub quoted_post_id: Option<i32>,
+    /// Timestamp when the post was last edited, if ever
+    #[schema(value_type = Option<String>, format = "date-time", example = "2025-04-19T07:45:00")]
+    pub edited_at: Option<NaiveDateTime>,
+    /// Number of times the post has been edited
+    pub edit_count: i32,
+    /// Timestamp when the post was deleted, if it was
+    #[schema(value_type = Option<String>, format = "date-time", example = "2025-04-19T08:00:00")]
+    pub deleted_at: Option<NaiveDateTime>,
+    /// ID of the user who deleted the post, if it was deleted
+    pub deleted_by: Option<i32>,
+    /// Timestamp when the post is scheduled to be published, while it is not yet
+    #[schema(value_type = Option<String>, format = "date-time", example = "2025-04-20T09:00:00")]
+    pub publish_at: Option<NaiveDateTime>,
+    /// Who can see the post
+    pub visibility: Visibility,
+    /// ID of the post this post replies to, if it is a reply
+    pub in_reply_to_id: Option<i32>,
+    /// ID of the post the conversation started with, which is the post's own ID if it
+    /// started it. Only missing between inserting a post and setting it.
+    pub conversation_id: Option<i32>,
+    /// Who can reply to the post
+    pub reply_audience: ReplyAudience,
+    /// Warning shown instead of the content until it is expanded, if any
+    pub content_warning: Option<String>,
+    /// Whether the author marked the post as sensitive
+    pub sensitive: bool,
+    /// Whether a moderator marked the post as sensitive, which the author cannot undo
+    pub sensitive_by_moderator: bool,
+    /// ISO 639-1 code of the language of the post, if known
+    pub lang: Option<String>,
+    /// Whether the author chose the language instead of it being detected
+    pub lang_overridden: bool,
+}
+
+/// Who can see a post
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, AsExpression, FromSqlRow, ToSchema,
+)]
+#[diesel(sql_type = Text)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    /// Everyone, and the post appears in feeds
+    Public,
+    /// Everyone, but the post is left out of feeds, hashtags and trends
+    Unlisted,
+    /// The author's followers and the users it mentions
+    Followers,
+    /// Only the users it mentions
+    Mentioned,
+}
+
+impl Visibility {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Visibility::Public => "public",
+            Visibility::Unlisted => "unlisted",
+            Visibility::Followers => "followers",
+            Visibility::Mentioned => "mentioned",
+        }
+    }
+}
+
+impl ToSql<Text, Pg> for Visibility {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(SqlIsNull::No)
+    }
+}
+
+impl FromSql<Text, Pg> for Visibility {
+    fn from_sql(value: PgValue<'_>) -> deserialize::Result<Self> {
+        match value.as_bytes() {
+            b"public" => Ok(Visibility::Public),
+            b"unlisted" => Ok(Visibility::Unlisted),
+            b"followers" => Ok(Visibility::Followers),
+            b"mentioned" => Ok(Visibility::Mentioned),
+            _ => Err("Unknown post visibility".into()),
+        }
+    }
+}
+
+/// Who can reply to a post, besides its author
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, AsExpression, FromSqlRow, ToSchema,
+)]
+#[diesel(sql_type = Text)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplyAudience {
+    /// Everyone who can see the post
+    Everyone,
+    /// The users the author follows and the users the post mentions
+    Following,
+    /// Only the users the post mentions
+    Mentioned,
+}
+
+impl ReplyAudience {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ReplyAudience::Everyone => "everyone",
+            ReplyAudience::Following => "following",
+            ReplyAudience::Mentioned => "mentioned",
+        }
+    }
+}
+
+impl ToSql<Text, Pg> for ReplyAudience {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(SqlIsNull::No)
+    }
+}
+
+impl FromSql<Text, Pg> for ReplyAudience {
+    fn from_sql(value: PgValue<'_>) -> deserialize::Result<Self> {
+        match value.as_bytes() {
+            b"everyone" => Ok(ReplyAudience::Everyone),
+            b"following" => Ok(ReplyAudience::Following),
+            b"mentioned" => Ok(ReplyAudience::Mentioned),
+            _ => Err("Unknown reply audience".into()),
+        }
+    }
+}
+
+/// Condition matching the posts that can be read
+pub type IsLive = And<IsNull<posts::deleted_at>, IsNull<posts::publish_at>>;
+
+impl Post {
+    /// Condition matching the posts that can be read, i.e. that are published and have
+    /// not been deleted, for use in queries joining posts
+    pub fn is_live() -> IsLive {
+        posts::deleted_at.is_null().and(posts::publish_at.is_null())
+    }
+
+    /// Query over the posts that can be read
+    pub fn live() -> posts::BoxedQuery<'static, Pg> {
+        posts::table.filter(Self::is_live()).into_boxed()
+    }
+
+    /// Condition matching the posts `viewer_id` is in the audience of, for use in
+    /// queries joining posts
+    ///
+    /// Authors see all their posts, followers see followers-only posts and mentioned
+    /// users see every post mentioning them.
+    pub fn is_visible_to<QS: 'static>(
+        viewer_id: i32,
+    ) -> Box<dyn BoxableExpression<QS, Pg, SqlType = Bool>>
+    where
+        posts::id: SelectableExpression<QS>,
+        posts::user_id: SelectableExpression<QS>,
+        posts::visibility: SelectableExpression<QS>,
+    {
+        let followed = follows::table
+            .filter(follows::follower_id.eq(viewer_id))
+            .select(follows::followee_id)
+            .into_boxed();
+        let mentioned_in = post_mentions::table
+            .filter(post_mentions::user_id.eq(viewer_id))
+            .select(post_mentions::post_id)
+            .into_boxed();
+
+        Box::new(
+            posts::visibility
+                .eq_any([Visibility::Public, Visibility::Unlisted])
+                .or(posts::user_id.eq(viewer_id))
+                .or(posts::visibility
+                    .eq(Visibility::Followers)
+                    .and(posts::user_id.eq_any(followed)))
+                .or(posts::id.eq_any(mentioned_in)),
+        )
+    }
+
+    /// Query over the posts that `viewer_id` can read
+    pub fn visible_to(viewer_id: i32) -> posts::BoxedQuery<'static, Pg> {
+        Self::live().filter(Self::is_visible_to(viewer_id))
+    }
+
+    /// Condition matching the posts that appear in feeds read by `viewer_id`, which
+    /// leave out unlisted posts of other users
+    pub fn is_listed_for<QS: 'static>(
+        viewer_id: i32,
+    ) -> Box<dyn BoxableExpression<QS, Pg, SqlType = Bool>>
+    where
+        posts::id: SelectableExpression<QS>,
+        posts::user_id: SelectableExpression<QS>,
+        posts::visibility: SelectableExpression<QS>,
+    {
+        Box::new(
+            Self::is_visible_to(viewer_id).and(
+                posts::visibility
+                    .ne(Visibility::Unlisted)
+                    .or(posts::user_id.eq(viewer_id)),
+            ),
+        )
+    }
+
+    /// Condition matching the posts `user_id` is allowed to reply to, if they can see
+    /// them, for use in queries joining posts
+    pub fn accepts_replies_from<QS: 'static>(
+        user_id: i32,
+    ) -> Box<dyn BoxableExpression<QS, Pg, SqlType = Bool>>
+    where
+        posts::id: SelectableExpression<QS>,
+        posts::user_id: SelectableExpression<QS>,
+        posts::reply_audience: SelectableExpression<QS>,
+    {
+        let following_user = follows::table
+            .filter(follows::followee_id.eq(user_id))
+            .select(follows::follower_id)
+            .into_boxed();
+        let mentioned_in = post_mentions::table
+            .filter(post_mentions::user_id.eq(user_id))
+            .select(post_mentions::post_id)
+            .into_boxed();
+
+        Box::new(
+            posts::reply_audience
+                .eq(ReplyAudience::Everyone)
+                .or(posts::user_id.eq(user_id))
+                .or(posts::reply_audience
+                    .eq(ReplyAudience::Following)
+                    .and(posts::user_id.eq_any(following_user)))
+                .or(posts::id.eq_any(mentioned_in)),
+        )
+    }
+
+    /// Condition matching the posts written in one of `languages`, and those whose
+    /// language is unknown if `include_unknown` is set, for use in queries joining posts
+    pub fn is_in_languages<QS: 'static>(
+        languages: Vec<String>,
+        include_unknown: bool,
+    ) -> Box<dyn BoxableExpression<QS, Pg, SqlType = Nullable<Bool>>>
+    where
+        posts::lang: SelectableExpression<QS>,
+    {
+        let in_languages = posts::lang.eq_any(languages);
+        if include_unknown {
+            Box::new(in_languages.or(posts::lang.is_null()))
+        } else {
+            Box::new(in_languages)
+        }
+    }
+
+    /// Indexes the hashtags, mentions and link card found in the content of the post
+    pub fn index_entities(&self, conn: &mut PgConnection) -> QueryResult<()> {
+        Hashtag::sync_post(conn, self.id, &self.content)?;
+        mention::sync_post(conn, self.id, &self.content)?;
+        link_card::sync_post(conn, self.id, &self.content)
+    }
 }
 
 /// Used for creating new posts in the database
@@ -36,4 +311,25 @@ pub struct NewPost {
     pub user_id: i32,
     /// Content of the tweet
     pub content: String,
+    /// ID of the post being quoted, if any
+    pub quoted_post_id: Option<i32>,
+    /// Timestamp when the post should be published, if it is scheduled
+    #[schema(value_type = Option<String>, format = "date-time", example = "2025-04-20T09:00:00")]
+    pub publish_at: Option<NaiveDateTime>,
+    /// Who can see the post
+    pub visibility: Visibility,
+    /// ID of the post being replied to, if any
+    pub in_reply_to_id: Option<i32>,
+    /// ID of the conversation of the post being replied to, if any
+    pub conversation_id: Option<i32>,
+    /// Who can reply to the post
+    pub reply_audience: ReplyAudience,
+    /// Warning shown instead of the content until it is expanded, if any
+    pub content_warning: Option<String>,
+    /// Whether the author marked the post as sensitive
+    pub sensitive: bool,
+    /// ISO 639-1 code of the language of the post, if known
+    pub lang: Option<String>,
+    /// Whether the author chose the language instead of it being detected
+    pub lang_overridden: bool,
 }
@@ -0,0 +1,51 @@
+use chrono::NaiveDateTime;
+use diesel::{Associations, Identifiable, Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::post::Post;
+use crate::schema::post_revisions;
+
+/// Represents a superseded version of a post in the database
+#[derive(Serialize, Deserialize, Queryable, Identifiable, Associations, Debug)]
+#[diesel(belongs_to(Post))]
+#[diesel(table_name = post_revisions)]
+pub struct PostRevision {
+    /// Unique identifier for the revision
+    pub id: i32,
+    /// ID of the revised post
+    pub post_id: i32,
+    /// Content of the post at the time
+    pub content: String,
+    /// Timestamp when this version of the post was published
+    pub created_at: NaiveDateTime,
+}
+
+/// Used for creating new revisions in the database
+#[derive(Insertable)]
+#[diesel(table_name = post_revisions)]
+pub struct NewPostRevision {
+    /// ID of the revised post
+    pub post_id: i32,
+    /// Content being superseded
+    pub content: String,
+    /// Timestamp when the superseded version was published
+    pub created_at: NaiveDateTime,
+}
+
+/// A single version of a post in its edit history
+#[derive(Serialize, Debug, ToSchema)]
+#[schema(example = json!({
+    "version": 0,
+    "content": "Hello wrold from Rust!",
+    "created_at": "2025-04-19T07:30:00"
+}))]
+pub struct PostVersion {
+    /// Version number, starting at 0 for the original post
+    pub version: i32,
+    /// Content of the post in this version
+    pub content: String,
+    /// Timestamp when this version was published
+    #[schema(value_type = String, format = "date-time", example = "2025-04-19T07:30:00")]
+    pub created_at: NaiveDateTime,
+}
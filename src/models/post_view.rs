@@ -0,0 +1,298 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::NaiveDateTime;
+use diesel::dsl::count_star;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::models::{
+    entity::Entity,
+    link_card::LinkCardView,
+    media::MediaView,
+    poll::PollView,
+    post::{Post, ReplyAudience, Visibility},
+    user::User,
+    user_settings::UserSettings,
+};
+use crate::schema::{likes, pinned_posts, posts, reposts, users};
+
+/// Represents a post as returned by the API, with author and engagement information
+#[derive(Serialize, Clone, Debug, ToSchema)]
+#[schema(example = json!({
+    "id": 2,
+    "user_id": 1,
+    "username": "johndoe",
+    "content": "Look at this!",
+    "entities": [],
+    "created_at": "2025-04-19T07:35:00",
+    "edited_at": null,
+    "edit_count": 0,
+    "repost_count": 3,
+    "quote_count": 0,
+    "like_count": 5,
+    "liked_by_me": true,
+    "quoted_post_id": 1,
+    "quoted_post": { "status": "unavailable" },
+    "in_reply_to_id": null,
+    "conversation_id": 2,
+    "media": [],
+    "card": null,
+    "poll": null,
+    "publish_at": null,
+    "visibility": "public",
+    "pinned": false,
+    "reply_audience": "everyone",
+    "can_reply": true,
+    "content_warning": null,
+    "sensitive": false,
+    "sensitive_by_moderator": false,
+    "expanded": true,
+    "lang": "en"
+}))]
+pub struct PostView {
+    /// Unique identifier for the post
+    pub id: i32,
+    /// ID of the user who created the post
+    pub user_id: i32,
+    /// Username of the user who created the post
+    pub username: String,
+    /// Content of the tweet
+    pub content: String,
+    /// Links, hashtags and mentions in the content, in order of appearance
+    pub entities: Vec<Entity>,
+    /// Timestamp when the post was created
+    #[schema(value_type = String, format = "date-time", example = "2025-04-19T07:30:00")]
+    pub created_at: NaiveDateTime,
+    /// Timestamp when the post was last edited, if ever
+    #[schema(value_type = Option<String>, format = "date-time", example = "2025-04-19T07:45:00")]
+    pub edited_at: Option<NaiveDateTime>,
+    /// Number of times the post has been edited
+    pub edit_count: i32,
+    /// Number of users who reposted the post
+    pub repost_count: i64,
+    /// Number of posts quoting the post
+    pub quote_count: i64,
+    /// Number of users who liked the post
+    pub like_count: i64,
+    /// Whether the requesting user liked the post
+    pub liked_by_me: bool,
+    /// ID of the post this post quotes, if it is a quote post
+    pub quoted_post_id: Option<i32>,
+    /// The quoted post, if this is a quote post
+    #[schema(no_recursion)]
+    pub quoted_post: Option<QuotedPost>,
+    /// ID of the post this post replies to, if it is a reply
+    pub in_reply_to_id: Option<i32>,
+    /// ID of the post the conversation started with, shared by all posts in it
+    pub conversation_id: i32,
+    /// Images attached to the post, in order
+    pub media: Vec<MediaView>,
+    /// Preview of the first link in the content, once it has been fetched, if the page
+    /// has one
+    pub card: Option<LinkCardView>,
+    /// The poll attached to the post, if any
+    pub poll: Option<PollView>,
+    /// Timestamp when the post is scheduled to be published, while it is not yet
+    #[schema(value_type = Option<String>, format = "date-time", example = "2025-04-20T09:00:00")]
+    pub publish_at: Option<NaiveDateTime>,
+    /// Who can see the post
+    pub visibility: Visibility,
+    /// Whether the author pinned the post to their profile
+    pub pinned: bool,
+    /// Who can reply to the post
+    pub reply_audience: ReplyAudience,
+    /// Whether the requesting user can reply to the post
+    pub can_reply: bool,
+    /// Warning shown instead of the content until it is expanded, if any
+    pub content_warning: Option<String>,
+    /// Whether the post is sensitive, as marked by its author or a moderator
+    pub sensitive: bool,
+    /// Whether a moderator marked the post as sensitive
+    pub sensitive_by_moderator: bool,
+    /// Whether to show the post expanded: posts with a content warning or marked as
+    /// sensitive are collapsed, unless the requesting user chose to expand them
+    pub expanded: bool,
+    /// ISO 639-1 code of the language of the post, if known
+    pub lang: Option<String>,
+}
+
+/// Post embedded in a quote post
+#[derive(Serialize, Clone, Debug, ToSchema)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum QuotedPost {
+    /// The quoted post can be shown
+    Available {
+        #[schema(no_recursion)]
+        post: Box<PostView>,
+    },
+    /// The quoted post has been deleted or cannot be shown
+    Unavailable,
+}
+
+impl PostView {
+    /// Builds the view of a single post as seen by `viewer_id`
+    pub fn load(conn: &mut PgConnection, viewer_id: i32, post: Post) -> QueryResult<PostView> {
+        let mut views = Self::load_many(conn, viewer_id, vec![post])?;
+        Ok(views.remove(0))
+    }
+
+    /// Builds the views of a list of posts as seen by `viewer_id`, keeping their order
+    pub fn load_many(
+        conn: &mut PgConnection,
+        viewer_id: i32,
+        posts: Vec<Post>,
+    ) -> QueryResult<Vec<PostView>> {
+        // Quoted posts are embedded one level deep only
+        let quoted_ids: Vec<i32> = posts
+            .iter()
+            .filter_map(|post| post.quoted_post_id)
+            .collect();
+        let quoted_posts = if quoted_ids.is_empty() {
+            Vec::new()
+        } else {
+            Post::visible_to(viewer_id)
+                .filter(posts::id.eq_any(&quoted_ids))
+                .load::<Post>(conn)?
+        };
+        let quoted_views: HashMap<i32, PostView> = Self::build(conn, viewer_id, quoted_posts)?
+            .into_iter()
+            .map(|view| (view.id, view))
+            .collect();
+
+        let mut views = Self::build(conn, viewer_id, posts)?;
+        for view in &mut views {
+            if let Some(quoted_id) = view.quoted_post_id {
+                // Several posts in the list may quote the same post
+                view.quoted_post = Some(match quoted_views.get(&quoted_id) {
+                    Some(quoted) => QuotedPost::Available {
+                        post: Box::new(quoted.clone()),
+                    },
+                    None => QuotedPost::Unavailable,
+                });
+            }
+        }
+
+        Ok(views)
+    }
+
+    fn build(
+        conn: &mut PgConnection,
+        viewer_id: i32,
+        posts: Vec<Post>,
+    ) -> QueryResult<Vec<PostView>> {
+        if posts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let post_ids: Vec<i32> = posts.iter().map(|post| post.id).collect();
+        let user_ids: Vec<i32> = posts.iter().map(|post| post.user_id).collect();
+
+        // Fetch the authors of all posts at once
+        let authors: HashMap<i32, User> = users::table
+            .filter(users::id.eq_any(&user_ids))
+            .load::<User>(conn)?
+            .into_iter()
+            .map(|user| (user.id, user))
+            .collect();
+
+        let repost_counts: HashMap<i32, i64> = reposts::table
+            .filter(reposts::post_id.eq_any(&post_ids))
+            .group_by(reposts::post_id)
+            .select((reposts::post_id, count_star()))
+            .load::<(i32, i64)>(conn)?
+            .into_iter()
+            .collect();
+
+        let quote_counts: HashMap<i32, i64> = posts::table
+            .filter(posts::quoted_post_id.eq_any(&post_ids))
+            .filter(Post::is_live())
+            .group_by(posts::quoted_post_id)
+            .select((posts::quoted_post_id, count_star()))
+            .load::<(Option<i32>, i64)>(conn)?
+            .into_iter()
+            .filter_map(|(post_id, count)| post_id.map(|post_id| (post_id, count)))
+            .collect();
+
+        let like_counts: HashMap<i32, i64> = likes::table
+            .filter(likes::post_id.eq_any(&post_ids))
+            .group_by(likes::post_id)
+            .select((likes::post_id, count_star()))
+            .load::<(i32, i64)>(conn)?
+            .into_iter()
+            .collect();
+
+        let mut entities = Entity::load_for_posts(conn, &posts)?;
+        let mut polls = PollView::load_for_posts(conn, viewer_id, &posts)?;
+        let mut media = MediaView::load_for_posts(conn, &post_ids)?;
+        let mut cards = LinkCardView::load_for_posts(conn, &post_ids)?;
+
+        let liked_by_viewer: HashSet<i32> = likes::table
+            .filter(likes::user_id.eq(viewer_id))
+            .filter(likes::post_id.eq_any(&post_ids))
+            .select(likes::post_id)
+            .load::<i32>(conn)?
+            .into_iter()
+            .collect();
+
+        let pinned: HashSet<i32> = pinned_posts::table
+            .filter(pinned_posts::post_id.eq_any(&post_ids))
+            .select(pinned_posts::post_id)
+            .load::<i32>(conn)?
+            .into_iter()
+            .collect();
+
+        let repliable: HashSet<i32> = posts::table
+            .filter(posts::id.eq_any(&post_ids))
+            .filter(Post::is_live())
+            .filter(Post::accepts_replies_from(viewer_id))
+            .select(posts::id)
+            .load::<i32>(conn)?
+            .into_iter()
+            .collect();
+
+        let expand_content_warnings = UserSettings::load(conn, viewer_id)?.expand_content_warnings;
+
+        Ok(posts
+            .into_iter()
+            .map(|post| PostView {
+                id: post.id,
+                user_id: post.user_id,
+                username: authors
+                    .get(&post.user_id)
+                    .map(|user| user.username.clone())
+                    .unwrap_or_default(),
+                entities: entities.remove(&post.id).unwrap_or_default(),
+                content: post.content,
+                created_at: post.created_at,
+                edited_at: post.edited_at,
+                edit_count: post.edit_count,
+                repost_count: repost_counts.get(&post.id).copied().unwrap_or(0),
+                quote_count: quote_counts.get(&post.id).copied().unwrap_or(0),
+                like_count: like_counts.get(&post.id).copied().unwrap_or(0),
+                liked_by_me: liked_by_viewer.contains(&post.id),
+                quoted_post_id: post.quoted_post_id,
+                quoted_post: None,
+                in_reply_to_id: post.in_reply_to_id,
+                conversation_id: post.conversation_id.unwrap_or(post.id),
+                media: media.remove(&post.id).unwrap_or_default(),
+                card: cards.remove(&post.id),
+                poll: polls.remove(&post.id),
+                publish_at: post.publish_at,
+                visibility: post.visibility,
+                pinned: pinned.contains(&post.id),
+                reply_audience: post.reply_audience,
+                can_reply: repliable.contains(&post.id),
+                expanded: expand_content_warnings
+                    || (post.content_warning.is_none()
+                        && !post.sensitive
+                        && !post.sensitive_by_moderator),
+                content_warning: post.content_warning,
+                sensitive: post.sensitive || post.sensitive_by_moderator,
+                sensitive_by_moderator: post.sensitive_by_moderator,
+                lang: post.lang,
+            })
+            .collect())
+    }
+}
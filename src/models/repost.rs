@@ -0,0 +1,15 @@
+use diesel::Insertable;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::schema::reposts;
+
+/// Used for creating new reposts in the database
+#[derive(Deserialize, Insertable, ToSchema)]
+#[diesel(table_name = reposts)]
+pub struct NewRepost {
+    /// ID of the user reposting
+    pub user_id: i32,
+    /// ID of the post being reposted
+    pub post_id: i32,
+}
@@ -0,0 +1,80 @@
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Insertable, Queryable};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::schema::{trend_entries, trend_snapshots};
+
+/// Represents one computation of trending hashtags for a time window in the database
+#[derive(Queryable, Identifiable, Debug)]
+#[diesel(table_name = trend_snapshots)]
+pub struct TrendSnapshot {
+    /// Unique identifier for the snapshot
+    pub id: i32,
+    /// Length of the window the trends were computed over, in minutes
+    pub window_minutes: i32,
+    /// Timestamp when the trends were computed
+    pub computed_at: NaiveDateTime,
+}
+
+/// Used for creating new trend snapshots in the database
+#[derive(Insertable)]
+#[diesel(table_name = trend_snapshots)]
+pub struct NewTrendSnapshot {
+    /// Length of the window the trends were computed over, in minutes
+    pub window_minutes: i32,
+    /// Timestamp when the trends were computed
+    pub computed_at: NaiveDateTime,
+}
+
+/// Used for creating new trend entries in the database
+#[derive(Insertable)]
+#[diesel(table_name = trend_entries)]
+pub struct NewTrendEntry {
+    /// ID of the snapshot the entry belongs to
+    pub snapshot_id: i32,
+    /// Position of the hashtag in the snapshot, starting at 1
+    pub rank: i32,
+    /// ID of the trending hashtag
+    pub hashtag_id: i32,
+    /// Trending score of the hashtag
+    pub score: f64,
+    /// Number of posts using the hashtag within the window
+    pub post_count: i32,
+    /// Number of distinct authors using the hashtag within the window
+    pub author_count: i32,
+}
+
+/// A trending hashtag as returned by the API
+#[derive(Serialize, Queryable, Debug, ToSchema)]
+#[schema(example = json!({
+    "rank": 1,
+    "hashtag": "rustlang",
+    "score": 4.2,
+    "post_count": 57,
+    "author_count": 31
+}))]
+pub struct Trend {
+    /// Position of the hashtag, starting at 1
+    pub rank: i32,
+    /// Normalized name of the hashtag, without the leading `#`
+    pub hashtag: String,
+    /// Trending score, how far above its usual volume the hashtag is
+    pub score: f64,
+    /// Number of posts using the hashtag within the window
+    pub post_count: i32,
+    /// Number of distinct authors using the hashtag within the window
+    pub author_count: i32,
+}
+
+/// Trending hashtags for a time window as returned by the API
+#[derive(Serialize, Debug, ToSchema)]
+pub struct TrendsResponse {
+    /// Length of the window the trends were computed over, in minutes
+    pub window_minutes: i32,
+    /// Timestamp when the trends were computed, `null` if they have not been yet
+    #[schema(value_type = Option<String>, format = "date-time", example = "2025-04-19T07:30:00")]
+    pub computed_at: Option<NaiveDateTime>,
+    /// Trending hashtags, highest score first
+    pub trends: Vec<Trend>,
+}
@@ -1,8 +1,11 @@
 use chrono::NaiveDateTime;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
 use diesel::{Identifiable, Insertable, Queryable};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use crate::models::post_view::PostView;
 use crate::schema::users;
 
 /// Represents a user in the database
@@ -24,6 +27,41 @@ pub struct User {
     /// Hashed password for authentication
     #[serde(skip_serializing)]
     pub password_hash: String,
+    /// Whether the user can moderate other users' posts
+    #[serde(skip_serializing)]
+    pub is_moderator: bool,
+}
+
+impl User {
+    /// Whether the user with the given ID is a moderator
+    pub fn moderator_status(conn: &mut PgConnection, user_id: i32) -> QueryResult<bool> {
+        users::table
+            .find(user_id)
+            .select(users::is_moderator)
+            .first::<bool>(conn)
+            .optional()
+            .map(|is_moderator| is_moderator.unwrap_or(false))
+    }
+}
+
+/// Profile of a user as returned by the API
+#[derive(Serialize, Debug, ToSchema)]
+#[schema(example = json!({
+    "id": 1,
+    "username": "johndoe",
+    "created_at": "2025-04-19T07:30:00",
+    "pinned_posts": []
+}))]
+pub struct UserProfile {
+    /// Unique identifier for the user
+    pub id: i32,
+    /// Username of the user
+    pub username: String,
+    /// Timestamp when the user was created
+    #[schema(value_type = String, format = "date-time", example = "2025-04-19T07:30:00")]
+    pub created_at: NaiveDateTime,
+    /// Posts the user pinned to their profile, in order
+    pub pinned_posts: Vec<PostView>,
 }
 
 /// Used for creating new users in the database
@@ -0,0 +1,54 @@
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::{AsChangeset, Insertable, Queryable};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::models::post::Visibility;
+use crate::schema::user_settings;
+
+/// Represents the settings chosen by a user in the database
+#[derive(Serialize, Queryable, Insertable, AsChangeset, Debug, ToSchema)]
+#[schema(example = json!({
+    "require_alt_text": true,
+    "default_visibility": "public",
+    "expand_content_warnings": false,
+    "preferred_languages": ["en", "fr"]
+}))]
+#[diesel(table_name = user_settings)]
+#[diesel(primary_key(user_id))]
+pub struct UserSettings {
+    /// ID of the user the settings belong to
+    #[serde(skip_serializing)]
+    pub user_id: i32,
+    /// Whether images must have alt text before the user can post them
+    pub require_alt_text: bool,
+    /// Visibility of new posts that do not give one
+    pub default_visibility: Visibility,
+    /// Whether posts with a content warning or marked as sensitive are shown expanded
+    pub expand_content_warnings: bool,
+    /// ISO 639-1 codes of the languages shown in the user's feed, all of them when empty
+    pub preferred_languages: Vec<String>,
+}
+
+impl UserSettings {
+    /// Settings of users who never changed them, matching the column defaults
+    pub fn defaults(user_id: i32) -> Self {
+        UserSettings {
+            user_id,
+            require_alt_text: false,
+            default_visibility: Visibility::Public,
+            expand_content_warnings: false,
+            preferred_languages: Vec::new(),
+        }
+    }
+
+    /// Loads the settings of a user, falling back to the defaults
+    pub fn load(conn: &mut PgConnection, user_id: i32) -> QueryResult<UserSettings> {
+        user_settings::table
+            .find(user_id)
+            .first::<UserSettings>(conn)
+            .optional()
+            .map(|settings| settings.unwrap_or_else(|| Self::defaults(user_id)))
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::schema::{post_view_counts, post_view_receipts};
+
+/// Used for remembering that a view was counted in the database
+#[derive(Insertable)]
+#[diesel(table_name = post_view_receipts)]
+pub struct NewViewReceipt {
+    /// ID of the viewed post
+    pub post_id: i32,
+    /// ID of the user who viewed the post
+    pub viewer_id: i32,
+    /// `impression` or `detail`
+    pub kind: &'static str,
+    /// Start of the window the view was counted in
+    pub window_start: NaiveDateTime,
+}
+
+/// Used for adding views to the hourly counts of a post in the database
+#[derive(Insertable)]
+#[diesel(table_name = post_view_counts)]
+pub struct NewViewCount {
+    /// ID of the viewed post
+    pub post_id: i32,
+    /// Start of the hour the views were counted in
+    pub hour: NaiveDateTime,
+    /// Number of times the post was shown in a list
+    pub impressions: i32,
+    /// Number of times the post itself was opened
+    pub detail_views: i32,
+}
+
+/// Views of a post during an hour, as returned by the API
+#[derive(Serialize, Queryable, Debug, ToSchema)]
+#[schema(example = json!({
+    "hour": "2025-04-19T07:00:00",
+    "impressions": 120,
+    "detail_views": 14
+}))]
+pub struct ViewCount {
+    /// Start of the hour
+    #[schema(value_type = String, format = "date-time", example = "2025-04-19T07:00:00")]
+    pub hour: NaiveDateTime,
+    /// Number of users the post was shown to in a list
+    pub impressions: i32,
+    /// Number of users who opened the post
+    pub detail_views: i32,
+}
+
+/// How a post performs, as returned by the API
+#[derive(Serialize, Debug, ToSchema)]
+#[schema(example = json!({
+    "post_id": 1,
+    "impressions": 120,
+    "detail_views": 14,
+    "engagements": 9,
+    "engagement_rate": 0.075,
+    "series": [
+        { "hour": "2025-04-19T07:00:00", "impressions": 120, "detail_views": 14 }
+    ]
+}))]
+pub struct PostAnalytics {
+    /// ID of the post
+    pub post_id: i32,
+    /// Number of times the post was shown in a list, counting each user once per
+    /// `VIEWS_DEDUP_WINDOW_MINUTES`
+    pub impressions: i64,
+    /// Number of times the post itself was opened, counting each user once per
+    /// `VIEWS_DEDUP_WINDOW_MINUTES`
+    pub detail_views: i64,
+    /// Number of likes, reposts, quotes and replies the post got
+    pub engagements: i64,
+    /// Engagements per impression, 0 before the first impression
+    pub engagement_rate: f64,
+    /// Views per hour over the requested period, oldest first. Hours without views
+    /// are left out.
+    pub series: Vec<ViewCount>,
+}
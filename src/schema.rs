@@ -1,11 +1,266 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    bookmark_folders (id) {
+        id -> Int4,
+        user_id -> Int4,
+        name -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    bookmarks (user_id, post_id) {
+        user_id -> Int4,
+        post_id -> Int4,
+        folder_id -> Nullable<Int4>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    drafts (id) {
+        id -> Int4,
+        user_id -> Int4,
+        content -> Varchar,
+        quoted_post_id -> Nullable<Int4>,
+        version -> Int4,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        in_reply_to_id -> Nullable<Int4>,
+        reply_audience -> Nullable<Varchar>,
+        visibility -> Nullable<Varchar>,
+        content_warning -> Nullable<Varchar>,
+        sensitive -> Bool,
+        lang -> Nullable<Varchar>,
+        media_ids -> Array<Int4>,
+        poll_options -> Nullable<Array<Text>>,
+        poll_duration_minutes -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    follows (follower_id, followee_id) {
+        follower_id -> Int4,
+        followee_id -> Int4,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    hashtags (id) {
+        id -> Int4,
+        name -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    idempotency_keys (user_id, key) {
+        user_id -> Int4,
+        key -> Varchar,
+        fingerprint -> Varchar,
+        status_code -> Nullable<Int4>,
+        content_type -> Nullable<Varchar>,
+        response_body -> Nullable<Bytea>,
+        created_at -> Timestamp,
+        claimed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    likes (user_id, post_id) {
+        user_id -> Int4,
+        post_id -> Int4,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     posts (id) {
         id -> Int4,
         user_id -> Int4,
         content -> Varchar,
         created_at -> Timestamp,
+        quoted_post_id -> Nullable<Int4>,
+        edited_at -> Nullable<Timestamp>,
+        edit_count -> Int4,
+        deleted_at -> Nullable<Timestamp>,
+        deleted_by -> Nullable<Int4>,
+        publish_at -> Nullable<Timestamp>,
+        visibility -> Varchar,
+        in_reply_to_id -> Nullable<Int4>,
+        conversation_id -> Nullable<Int4>,
+        reply_audience -> Varchar,
+        content_warning -> Nullable<Varchar>,
+        sensitive -> Bool,
+        sensitive_by_moderator -> Bool,
+        lang -> Nullable<Varchar>,
+        lang_overridden -> Bool,
+    }
+}
+
+diesel::table! {
+    link_cards (id) {
+        id -> Int4,
+        url -> Varchar,
+        card_type -> Nullable<Varchar>,
+        title -> Nullable<Varchar>,
+        description -> Nullable<Varchar>,
+        image_url -> Nullable<Varchar>,
+        site_name -> Nullable<Varchar>,
+        fetched_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        fetch_error -> Nullable<Varchar>,
+        attempts -> Int4,
+        next_fetch_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    media (id) {
+        id -> Int4,
+        user_id -> Int4,
+        post_id -> Nullable<Int4>,
+        position -> Nullable<Int4>,
+        content_type -> Varchar,
+        width -> Int4,
+        height -> Int4,
+        size_bytes -> Int4,
+        storage_key -> Varchar,
+        created_at -> Timestamp,
+        blurhash -> Nullable<Varchar>,
+        dominant_color -> Nullable<Varchar>,
+        alt_text -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    media_variants (media_id, name) {
+        media_id -> Int4,
+        name -> Varchar,
+        width -> Int4,
+        height -> Int4,
+        storage_key -> Varchar,
+    }
+}
+
+diesel::table! {
+    pinned_posts (post_id) {
+        post_id -> Int4,
+        user_id -> Int4,
+        position -> Int4,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    poll_options (id) {
+        id -> Int4,
+        post_id -> Int4,
+        position -> Int4,
+        label -> Varchar,
+    }
+}
+
+diesel::table! {
+    poll_votes (post_id, user_id) {
+        post_id -> Int4,
+        user_id -> Int4,
+        option_id -> Int4,
+        voted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    polls (post_id) {
+        post_id -> Int4,
+        duration_minutes -> Int4,
+    }
+}
+
+diesel::table! {
+    post_cards (post_id) {
+        post_id -> Int4,
+        link_card_id -> Int4,
+    }
+}
+
+diesel::table! {
+    post_hashtags (post_id, hashtag_id) {
+        post_id -> Int4,
+        hashtag_id -> Int4,
+    }
+}
+
+diesel::table! {
+    post_mentions (post_id, user_id) {
+        post_id -> Int4,
+        user_id -> Int4,
+    }
+}
+
+diesel::table! {
+    post_revisions (id) {
+        id -> Int4,
+        post_id -> Int4,
+        content -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    post_view_counts (post_id, hour) {
+        post_id -> Int4,
+        hour -> Timestamp,
+        impressions -> Int4,
+        detail_views -> Int4,
+    }
+}
+
+diesel::table! {
+    post_view_receipts (post_id, viewer_id, kind, window_start) {
+        post_id -> Int4,
+        viewer_id -> Int4,
+        kind -> Varchar,
+        window_start -> Timestamp,
+    }
+}
+
+diesel::table! {
+    reposts (user_id, post_id) {
+        user_id -> Int4,
+        post_id -> Int4,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    trend_entries (snapshot_id, rank) {
+        snapshot_id -> Int4,
+        rank -> Int4,
+        hashtag_id -> Int4,
+        score -> Float8,
+        post_count -> Int4,
+        author_count -> Int4,
+    }
+}
+
+diesel::table! {
+    trend_snapshots (id) {
+        id -> Int4,
+        window_minutes -> Int4,
+        computed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    user_settings (user_id) {
+        user_id -> Int4,
+        require_alt_text -> Bool,
+        default_visibility -> Varchar,
+        expand_content_warnings -> Bool,
+        preferred_languages -> Array<Text>,
     }
 }
 
@@ -15,9 +270,70 @@ diesel::table! {
         username -> Varchar,
         created_at -> Timestamp,
         password_hash -> Varchar,
+        is_moderator -> Bool,
     }
 }
 
+diesel::joinable!(bookmark_folders -> users (user_id));
+diesel::joinable!(bookmarks -> bookmark_folders (folder_id));
+diesel::joinable!(bookmarks -> posts (post_id));
+diesel::joinable!(bookmarks -> users (user_id));
+diesel::joinable!(drafts -> users (user_id));
+diesel::joinable!(idempotency_keys -> users (user_id));
+diesel::joinable!(likes -> posts (post_id));
+diesel::joinable!(likes -> users (user_id));
+diesel::joinable!(media -> posts (post_id));
+diesel::joinable!(media -> users (user_id));
+diesel::joinable!(media_variants -> media (media_id));
+diesel::joinable!(pinned_posts -> posts (post_id));
+diesel::joinable!(pinned_posts -> users (user_id));
+diesel::joinable!(poll_options -> polls (post_id));
+diesel::joinable!(poll_votes -> poll_options (option_id));
+diesel::joinable!(poll_votes -> polls (post_id));
+diesel::joinable!(poll_votes -> users (user_id));
+diesel::joinable!(polls -> posts (post_id));
+diesel::joinable!(post_cards -> link_cards (link_card_id));
+diesel::joinable!(post_cards -> posts (post_id));
+diesel::joinable!(post_hashtags -> hashtags (hashtag_id));
+diesel::joinable!(post_hashtags -> posts (post_id));
+diesel::joinable!(post_mentions -> posts (post_id));
+diesel::joinable!(post_mentions -> users (user_id));
+diesel::joinable!(post_revisions -> posts (post_id));
+diesel::joinable!(post_view_counts -> posts (post_id));
+diesel::joinable!(post_view_receipts -> posts (post_id));
+diesel::joinable!(post_view_receipts -> users (viewer_id));
 diesel::joinable!(posts -> users (user_id));
+diesel::joinable!(reposts -> posts (post_id));
+diesel::joinable!(reposts -> users (user_id));
+diesel::joinable!(trend_entries -> hashtags (hashtag_id));
+diesel::joinable!(trend_entries -> trend_snapshots (snapshot_id));
+diesel::joinable!(user_settings -> users (user_id));
 
-diesel::allow_tables_to_appear_in_same_query!(posts, users,);
+diesel::allow_tables_to_appear_in_same_query!(
+    bookmark_folders,
+    bookmarks,
+    drafts,
+    follows,
+    hashtags,
+    idempotency_keys,
+    likes,
+    link_cards,
+    media,
+    media_variants,
+    pinned_posts,
+    poll_options,
+    poll_votes,
+    polls,
+    post_cards,
+    post_hashtags,
+    post_mentions,
+    post_revisions,
+    post_view_counts,
+    post_view_receipts,
+    posts,
+    reposts,
+    trend_entries,
+    trend_snapshots,
+    user_settings,
+    users,
+);
@@ -0,0 +1,124 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::storage::{MediaStore, invalid_key, is_valid_key};
+use crate::util::config;
+
+/// Keeps media files in a local directory
+pub struct FilesystemStore {
+    root: PathBuf,
+}
+
+impl FilesystemStore {
+    /// Creates a store keeping files under `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Creates a store keeping files under `MEDIA_ROOT`
+    pub fn from_env() -> Self {
+        Self::new(config::env_or("MEDIA_ROOT", "media".to_string()))
+    }
+
+    fn path(&self, key: &str) -> io::Result<PathBuf> {
+        if !is_valid_key(key) {
+            return Err(invalid_key());
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+impl MediaStore for FilesystemStore {
+    fn put(&self, key: &str, _content_type: &str, bytes: &[u8]) -> io::Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Write to a temporary file first so readers never see a partial file
+        let temporary_path = path.with_extension("partial");
+        fs::write(&temporary_path, bytes)?;
+        fs::rename(&temporary_path, &path)
+    }
+
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.path(key)?) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn delete(&self, key: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(key)?) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn temporary_store() -> (FilesystemStore, PathBuf) {
+        let root = std::env::temp_dir().join(format!("media-test-{}", Uuid::new_v4()));
+        (FilesystemStore::new(&root), root)
+    }
+
+    #[test]
+    fn files_can_be_stored_read_and_deleted() {
+        let (store, root) = temporary_store();
+
+        store
+            .put("media/1/original.png", "image/png", b"png")
+            .unwrap();
+        assert_eq!(store.get("media/1/original.png").unwrap().unwrap(), b"png");
+        assert!(root.join("media/1/original.png").is_file());
+        assert!(!root.join("media/1/original.partial").exists());
+
+        store
+            .put("media/1/original.png", "image/png", b"new")
+            .unwrap();
+        assert_eq!(store.get("media/1/original.png").unwrap().unwrap(), b"new");
+
+        store.delete("media/1/original.png").unwrap();
+        assert!(store.get("media/1/original.png").unwrap().is_none());
+        // Deleting a missing file is not an error
+        store.delete("media/1/original.png").unwrap();
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn keys_outside_the_root_are_refused() {
+        let (store, root) = temporary_store();
+        let escaped = root.with_file_name(format!(
+            "{}-escaped",
+            root.file_name().unwrap().to_string_lossy()
+        ));
+        let escaped_key = format!("../{}", escaped.file_name().unwrap().to_string_lossy());
+
+        for key in [
+            escaped_key.as_str(),
+            "/tmp/escaped",
+            "media/../../escaped",
+            "",
+        ] {
+            let error = store.put(key, "image/png", b"png").unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput, "{:?}", key);
+            assert_eq!(
+                store.get(key).unwrap_err().kind(),
+                io::ErrorKind::InvalidInput
+            );
+            assert_eq!(
+                store.delete(key).unwrap_err().kind(),
+                io::ErrorKind::InvalidInput
+            );
+        }
+        assert!(!escaped.exists());
+        assert!(!root.exists());
+    }
+}
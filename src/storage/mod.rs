@@ -0,0 +1,104 @@
+use std::io;
+use std::sync::Arc;
+
+use crate::util::config;
+
+// Export media stores
+pub mod filesystem;
+pub mod s3;
+
+/// Where uploaded media files are kept
+///
+/// Keys are relative paths like `media/<uuid>/small.jpg`. Implementations block, so
+/// they are meant to be called from `web::block`.
+pub trait MediaStore: Send + Sync {
+    /// Stores `bytes` under `key`, replacing any file already there
+    fn put(&self, key: &str, content_type: &str, bytes: &[u8]) -> io::Result<()>;
+
+    /// Reads the file stored under `key`, if there is one
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+
+    /// Removes the file stored under `key`, if there is one
+    fn delete(&self, key: &str) -> io::Result<()>;
+}
+
+/// Creates the media store selected by `MEDIA_STORE` (`filesystem` or `s3`)
+pub fn from_env() -> Arc<dyn MediaStore> {
+    match config::env_or("MEDIA_STORE", "filesystem".to_string()).as_str() {
+        "s3" => Arc::new(s3::S3Store::from_env()),
+        "filesystem" => Arc::new(filesystem::FilesystemStore::from_env()),
+        other => panic!("Unknown MEDIA_STORE: {}", other),
+    }
+}
+
+/// Removes the files stored under `keys`, reporting the ones that could not be removed
+///
+/// Meant for files whose database rows are already gone, so a failure only leaves an
+/// unreachable file behind.
+pub fn delete_all(store: &dyn MediaStore, keys: &[String]) {
+    for key in keys {
+        if let Err(e) = store.delete(key) {
+            eprintln!("Failed to delete media file {}: {}", key, e);
+        }
+    }
+}
+
+/// URL clients fetch the file stored under `key` from
+///
+/// Defaults to the server's own `/media/files` route, `MEDIA_BASE_URL` can point to a
+/// CDN or bucket instead.
+pub fn public_url(key: &str) -> String {
+    let base_url = config::env_or("MEDIA_BASE_URL", "/media/files".to_string());
+    format!("{}/{}", base_url.trim_end_matches('/'), key)
+}
+
+/// Whether `key` is a safe relative path, without `..` or empty segments
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        })
+}
+
+// Error returned for keys that are not safe relative paths
+fn invalid_key() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "Invalid media key")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_must_be_safe_relative_paths() {
+        let cases = [
+            ("media/0b7e/original.png", true),
+            ("media/0b7e/small.webp", true),
+            ("a", true),
+            ("file-name_1.tar.gz", true),
+            ("", false),
+            ("/etc/passwd", false),
+            ("media/", false),
+            ("media//original.png", false),
+            ("..", false),
+            ("../secret", false),
+            ("media/../../secret", false),
+            ("media/..", false),
+            ("./media", false),
+            ("media\\..\\secret", false),
+            ("media/%2e%2e/secret", false),
+            ("media/original png", false),
+            ("media/ünïcode.png", false),
+            ("media/original.png\0", false),
+        ];
+
+        for (key, valid) in cases {
+            assert_eq!(is_valid_key(key), valid, "{:?}", key);
+        }
+    }
+}
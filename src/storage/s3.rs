@@ -0,0 +1,309 @@
+use std::io::{self, Read};
+use std::time::Duration;
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::storage::{MediaStore, invalid_key, is_valid_key};
+use crate::util::config;
+
+/// Keeps media files in a bucket of an S3-compatible object store
+///
+/// Requests use path-style URLs (`<endpoint>/<bucket>/<key>`) signed with AWS
+/// Signature Version 4, which works with AWS S3 as well as MinIO and similar servers.
+pub struct S3Store {
+    agent: ureq::Agent,
+    endpoint: String,
+    bucket: String,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+impl S3Store {
+    /// Creates a store using the given bucket and credentials
+    pub fn new(
+        endpoint: &str,
+        bucket: &str,
+        region: &str,
+        access_key_id: &str,
+        secret_access_key: &str,
+    ) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(Duration::from_secs(5))
+            .timeout(Duration::from_secs(30))
+            .build();
+
+        Self {
+            agent,
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            bucket: bucket.to_string(),
+            region: region.to_string(),
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.to_string(),
+        }
+    }
+
+    /// Creates a store from `S3_ENDPOINT`, `S3_BUCKET`, `S3_REGION`,
+    /// `S3_ACCESS_KEY_ID` and `S3_SECRET_ACCESS_KEY`
+    pub fn from_env() -> Self {
+        let required = |key: &str| {
+            std::env::var(key).unwrap_or_else(|_| panic!("{} must be set for the S3 store", key))
+        };
+
+        Self::new(
+            &required("S3_ENDPOINT"),
+            &required("S3_BUCKET"),
+            &config::env_or("S3_REGION", "us-east-1".to_string()),
+            &required("S3_ACCESS_KEY_ID"),
+            &required("S3_SECRET_ACCESS_KEY"),
+        )
+    }
+
+    // Builds a signed request for the object stored under `key`
+    fn request(&self, method: &str, key: &str, payload: &[u8]) -> io::Result<ureq::Request> {
+        if !is_valid_key(key) {
+            return Err(invalid_key());
+        }
+
+        let path = format!("/{}/{}", self.bucket, key);
+        let host = self
+            .endpoint
+            .split_once("://")
+            .map_or(self.endpoint.as_str(), |(_, rest)| rest)
+            .split('/')
+            .next()
+            .unwrap_or_default();
+
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(Sha256::digest(payload));
+
+        let canonical_request = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method, path, host, payload_hash, amz_date, SIGNED_HEADERS, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let signing_key = [self.region.as_str(), "s3", "aws4_request"].iter().fold(
+            hmac_sha256(
+                format!("AWS4{}", self.secret_access_key).as_bytes(),
+                date.as_bytes(),
+            ),
+            |key, part| hmac_sha256(&key, part.as_bytes()),
+        );
+        let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id, scope, SIGNED_HEADERS, signature
+        );
+
+        Ok(self
+            .agent
+            .request(method, &format!("{}{}", self.endpoint, path))
+            .set("x-amz-content-sha256", &payload_hash)
+            .set("x-amz-date", &amz_date)
+            .set("Authorization", &authorization))
+    }
+}
+
+// Headers covered by request signatures
+const SIGNED_HEADERS: &str = "host;x-amz-content-sha256;x-amz-date";
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn to_io_error(error: ureq::Error) -> io::Error {
+    io::Error::other(error.to_string())
+}
+
+impl MediaStore for S3Store {
+    fn put(&self, key: &str, content_type: &str, bytes: &[u8]) -> io::Result<()> {
+        self.request("PUT", key, bytes)?
+            .set("Content-Type", content_type)
+            .send_bytes(bytes)
+            .map_err(to_io_error)?;
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match self.request("GET", key, &[])?.call() {
+            Ok(response) => {
+                let mut bytes = Vec::new();
+                response.into_reader().read_to_end(&mut bytes)?;
+                Ok(Some(bytes))
+            }
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(e) => Err(to_io_error(e)),
+        }
+    }
+
+    fn delete(&self, key: &str) -> io::Result<()> {
+        match self.request("DELETE", key, &[])?.call() {
+            Ok(_) | Err(ureq::Error::Status(404, _)) => Ok(()),
+            Err(e) => Err(to_io_error(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use crate::util::test_server::{self, Request, Response};
+
+    const ACCESS_KEY_ID: &str = "AKIDEXAMPLE";
+    const SECRET_ACCESS_KEY: &str = "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY";
+
+    // Checks the signature of a request the way S3 does, from the headers received
+    fn is_signed(req: &Request) -> bool {
+        let Some(authorization) = req
+            .header("authorization")
+            .and_then(|authorization| authorization.strip_prefix("AWS4-HMAC-SHA256 "))
+        else {
+            return false;
+        };
+        let fields: HashMap<&str, &str> = authorization
+            .split(", ")
+            .filter_map(|field| field.split_once('='))
+            .collect();
+        let (Some(credential), Some(signed_headers), Some(signature)) = (
+            fields.get("Credential"),
+            fields.get("SignedHeaders"),
+            fields.get("Signature"),
+        ) else {
+            return false;
+        };
+        let Some((access_key_id, scope)) = credential.split_once('/') else {
+            return false;
+        };
+        let (Some(amz_date), Some(payload_hash)) =
+            (req.header("x-amz-date"), req.header("x-amz-content-sha256"))
+        else {
+            return false;
+        };
+        if access_key_id != ACCESS_KEY_ID
+            || payload_hash != hex::encode(Sha256::digest(&req.body))
+            || !scope.starts_with(&amz_date[..8])
+            || !scope.ends_with("/us-east-1/s3/aws4_request")
+        {
+            return false;
+        }
+
+        let mut canonical_request = format!("{}\n{}\n\n", req.method, req.path);
+        for name in signed_headers.split(';') {
+            canonical_request.push_str(&format!("{}:{}\n", name, req.header(name).unwrap_or("")));
+        }
+        canonical_request.push_str(&format!("\n{}\n{}", signed_headers, payload_hash));
+
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let mut signing_key = format!("AWS4{}", SECRET_ACCESS_KEY).into_bytes();
+        for part in scope.split('/') {
+            signing_key = hmac_sha256(&signing_key, part.as_bytes());
+        }
+
+        hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes())) == *signature
+    }
+
+    // Serves a bucket named `media`, kept in memory, to signed requests only
+    fn fake_s3() -> String {
+        let objects: Arc<Mutex<HashMap<String, Vec<u8>>>> = Arc::default();
+
+        test_server::serve(move |req| {
+            if !is_signed(req) {
+                return Response::new(403);
+            }
+            let Some(key) = req.path.strip_prefix("/media/") else {
+                return Response::new(404);
+            };
+
+            let mut objects = objects.lock().unwrap();
+            match req.method.as_str() {
+                "PUT" if req.header("content-type").is_none() => Response::new(400),
+                "PUT" => {
+                    objects.insert(key.to_string(), req.body.clone());
+                    Response::new(200)
+                }
+                "GET" => match objects.get(key) {
+                    Some(bytes) => Response::new(200).body(bytes.clone()),
+                    None => Response::new(404),
+                },
+                "DELETE" => {
+                    objects.remove(key);
+                    Response::new(204)
+                }
+                _ => Response::new(405),
+            }
+        })
+    }
+
+    #[test]
+    fn files_can_be_stored_read_and_deleted() {
+        let endpoint = fake_s3();
+        let store = S3Store::new(
+            &endpoint,
+            "media",
+            "us-east-1",
+            ACCESS_KEY_ID,
+            SECRET_ACCESS_KEY,
+        );
+
+        store
+            .put("media/1/original.png", "image/png", b"png")
+            .unwrap();
+        assert_eq!(store.get("media/1/original.png").unwrap().unwrap(), b"png");
+        assert!(store.get("media/2/original.png").unwrap().is_none());
+
+        store.delete("media/1/original.png").unwrap();
+        assert!(store.get("media/1/original.png").unwrap().is_none());
+        store.delete("media/1/original.png").unwrap();
+    }
+
+    #[test]
+    fn requests_are_refused_with_the_wrong_secret() {
+        let endpoint = fake_s3();
+        let store = S3Store::new(&endpoint, "media", "us-east-1", ACCESS_KEY_ID, "wrong");
+
+        assert!(
+            store
+                .put("media/1/original.png", "image/png", b"png")
+                .is_err()
+        );
+        assert!(store.get("media/1/original.png").is_err());
+    }
+
+    #[test]
+    fn invalid_keys_are_refused_before_any_request() {
+        let store = S3Store::new(
+            "http://127.0.0.1:9",
+            "media",
+            "us-east-1",
+            ACCESS_KEY_ID,
+            SECRET_ACCESS_KEY,
+        );
+
+        for key in ["../other-bucket/file", "media/../../file", "/file", ""] {
+            let error = store.put(key, "image/png", b"png").unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput, "{:?}", key);
+        }
+    }
+}
@@ -1,9 +1,30 @@
 use utoipa::{OpenApi, Modify, openapi::security::{SecurityScheme, Http, HttpAuthScheme}};
 
 use crate::{
+    controllers::analytics_controller,
     controllers::auth_controller,
+    controllers::bookmark_controller,
+    controllers::draft_controller,
+    controllers::follow_controller,
+    controllers::hashtag_controller,
+    controllers::like_controller,
+    controllers::media_controller,
+    controllers::mention_controller,
+    controllers::moderation_controller,
+    controllers::pin_controller,
+    controllers::poll_controller,
     controllers::post_controller,
-    models::{post, user}
+    controllers::repost_controller,
+    controllers::scheduled_post_controller,
+    controllers::settings_controller,
+    controllers::thread_controller,
+    controllers::trend_controller,
+    controllers::user_controller,
+    models::{
+        bookmark, draft, entity, link_card, media, poll, post, post_revision, post_view, trend, user,
+        user_settings, view_count,
+    },
+    util::content,
 };
 
 // Define security scheme modifier for OpenAPI docs
@@ -31,10 +52,95 @@ impl Modify for SecurityAddon {
         post_controller::create_post,
         post_controller::update_post,
         post_controller::delete_post,
+        post_controller::get_post_history,
+        post_controller::restore_post,
+        post_controller::update_post_visibility,
+        post_controller::update_reply_audience,
+        post_controller::update_content_warning,
+        post_controller::update_post_language,
+        analytics_controller::get_post_analytics,
+        thread_controller::create_thread,
+        scheduled_post_controller::get_scheduled_posts,
+        scheduled_post_controller::reschedule_post,
+        scheduled_post_controller::cancel_scheduled_post,
+        draft_controller::get_drafts,
+        draft_controller::create_draft,
+        draft_controller::update_draft,
+        draft_controller::delete_draft,
+        draft_controller::publish_draft,
+        repost_controller::repost_post,
+        repost_controller::undo_repost,
+        poll_controller::vote_in_poll,
+        media_controller::upload_media,
+        media_controller::update_media,
+        media_controller::get_media_file,
+        like_controller::like_post,
+        like_controller::unlike_post,
+        like_controller::get_post_likes,
+        like_controller::get_user_likes,
+        mention_controller::get_my_mentions,
+        follow_controller::follow_user,
+        follow_controller::unfollow_user,
+        user_controller::get_user,
+        user_controller::get_user_posts,
+        pin_controller::pin_post,
+        pin_controller::unpin_post,
+        pin_controller::reorder_pins,
+        settings_controller::get_settings,
+        settings_controller::update_settings,
+        bookmark_controller::bookmark_post,
+        bookmark_controller::remove_bookmark,
+        bookmark_controller::get_bookmarks,
+        bookmark_controller::get_bookmark_folders,
+        bookmark_controller::create_bookmark_folder,
+        bookmark_controller::delete_bookmark_folder,
+        moderation_controller::get_deleted_posts,
+        moderation_controller::mark_post_sensitive,
+        hashtag_controller::get_hashtag_posts,
+        trend_controller::get_trends,
     ),
     components(schemas(
         post::Post, 
+        entity::Entity,
+        entity::EntityType,
+        post_view::PostView,
+        post_view::QuotedPost,
+        post::Visibility,
+        post::ReplyAudience,
+        poll::PollView,
+        media::MediaView,
+        media::MediaVariantView,
+        link_card::LinkCardView,
+        poll::PollOptionView,
+        bookmark::BookmarkFolder,
+        draft::Draft,
+        draft::DraftPoll,
+        post_revision::PostVersion,
+        content::ContentError,
+        content::ContentErrorResponse,
+        trend::Trend,
+        trend::TrendsResponse,
+        view_count::PostAnalytics,
+        view_count::ViewCount,
+        post_controller::CreatePostRequest,
+        post_controller::PollRequest,
+        post_controller::UpdateVisibilityRequest,
+        post_controller::UpdateReplyAudienceRequest,
+        post_controller::UpdateContentWarningRequest,
+        post_controller::UpdateLanguageRequest,
+        moderation_controller::MarkSensitiveRequest,
+        thread_controller::CreateThreadRequest,
+        pin_controller::ReorderPinsRequest,
+        poll_controller::VoteRequest,
+        media_controller::UploadMediaForm,
+        media_controller::UpdateMediaRequest,
+        settings_controller::UpdateSettingsRequest,
+        scheduled_post_controller::ReschedulePostRequest,
+        draft_controller::CreateDraftRequest,
+        draft_controller::UpdateDraftRequest,
         user::User,
+        user::UserProfile,
+        user_settings::UserSettings,
         auth_controller::LoginRequest,
         auth_controller::RegisterRequest,
         auth_controller::AuthResponse,
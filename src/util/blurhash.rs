@@ -0,0 +1,145 @@
+use std::f32::consts::PI;
+
+use image::RgbImage;
+
+// Alphabet of the base 83 encoding used by BlurHash, see https://blurha.sh
+const BASE83: &[u8] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+/// Encodes an image as a BlurHash with the given number of components along each
+/// axis, between 1 and 9. The image should already be small, as every pixel is
+/// visited once per component.
+pub fn encode(image: &RgbImage, x_components: u32, y_components: u32) -> String {
+    let (width, height) = image.dimensions();
+
+    // Linear values of the image, converted once
+    let pixels: Vec<[f32; 3]> = image
+        .pixels()
+        .map(|pixel| pixel.0.map(srgb_to_linear))
+        .collect();
+
+    let mut factors = Vec::with_capacity((x_components * y_components) as usize);
+    for j in 0..y_components {
+        for i in 0..x_components {
+            let normalisation = if i == 0 && j == 0 { 1.0 } else { 2.0 };
+            let mut factor = [0.0f32; 3];
+            for y in 0..height {
+                let basis_y = (PI * j as f32 * y as f32 / height as f32).cos();
+                for x in 0..width {
+                    let basis = basis_y * (PI * i as f32 * x as f32 / width as f32).cos();
+                    let pixel = pixels[(y * width + x) as usize];
+                    for (value, channel) in factor.iter_mut().zip(pixel) {
+                        *value += basis * channel;
+                    }
+                }
+            }
+            let scale = normalisation / (width * height) as f32;
+            factors.push(factor.map(|value| value * scale));
+        }
+    }
+
+    let (dc, ac) = factors.split_first().unwrap();
+
+    let mut hash = String::new();
+    let size_flag = (x_components - 1) + (y_components - 1) * 9;
+    push_base83(&mut hash, size_flag, 1);
+
+    let max_value = if ac.is_empty() {
+        push_base83(&mut hash, 0, 1);
+        1.0
+    } else {
+        let actual_max = ac
+            .iter()
+            .flatten()
+            .fold(0.0f32, |max, value| max.max(value.abs()));
+        let quantised_max = ((actual_max * 166.0 - 0.5).floor()).clamp(0.0, 82.0) as u32;
+        push_base83(&mut hash, quantised_max, 1);
+        (quantised_max + 1) as f32 / 166.0
+    };
+
+    let [r, g, b] = dc.map(linear_to_srgb);
+    push_base83(&mut hash, (r << 16) + (g << 8) + b, 4);
+
+    for factor in ac {
+        let [r, g, b] = factor.map(|value| {
+            let quantised = (sign_pow(value / max_value, 0.5) * 9.0 + 9.5).floor();
+            quantised.clamp(0.0, 18.0) as u32
+        });
+        push_base83(&mut hash, r * 19 * 19 + g * 19 + b, 2);
+    }
+
+    hash
+}
+
+fn push_base83(hash: &mut String, value: u32, length: u32) {
+    for i in 1..=length {
+        let digit = (value / 83u32.pow(length - i)) % 83;
+        hash.push(BASE83[digit as usize] as char);
+    }
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> u32 {
+    let value = value.clamp(0.0, 1.0);
+    if value <= 0.0031308 {
+        (value * 12.92 * 255.0 + 0.5) as u32
+    } else {
+        ((1.055 * value.powf(1.0 / 2.4) - 0.055) * 255.0 + 0.5) as u32
+    }
+}
+
+fn sign_pow(value: f32, exponent: f32) -> f32 {
+    value.abs().powf(exponent).copysign(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgb;
+
+    // A gradient with a red square in it
+    fn test_image() -> RgbImage {
+        RgbImage::from_fn(16, 12, |x, y| {
+            if (4..8).contains(&x) && (3..7).contains(&y) {
+                Rgb([230, 40, 30])
+            } else {
+                Rgb([(x * 16) as u8, (y * 20) as u8, ((x + y) * 6) as u8])
+            }
+        })
+    }
+
+    // Reference hashes computed with the encoder published at https://blurha.sh
+    #[test]
+    fn encode_matches_the_reference_encoder() {
+        assert_eq!(encode(&test_image(), 4, 3), "LjID{T2+fkKNy{WEW;R,O-SgsAwd");
+        assert_eq!(encode(&test_image(), 3, 4), "TjID{T2+fky{WEW;O-SgsAS}OBsA");
+    }
+
+    #[test]
+    fn encode_keeps_only_the_average_colour_with_one_component() {
+        let image = RgbImage::from_pixel(8, 8, Rgb([200, 100, 50]));
+        assert_eq!(encode(&image, 1, 1), "00M|T9");
+    }
+
+    #[test]
+    fn encode_length_depends_on_the_components() {
+        for (x_components, y_components) in [(1, 1), (4, 3), (9, 9)] {
+            let hash = encode(&test_image(), x_components, y_components);
+            assert_eq!(
+                hash.len(),
+                6 + 2 * (x_components * y_components - 1) as usize,
+                "{}x{}",
+                x_components,
+                y_components
+            );
+        }
+    }
+}
@@ -0,0 +1,10 @@
+use std::env;
+use std::str::FromStr;
+
+/// Reads a setting from the environment, falling back to `default` when unset or invalid
+pub fn env_or<T: FromStr>(key: &str, default: T) -> T {
+    env::var(key)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
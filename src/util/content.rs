@@ -0,0 +1,296 @@
+use serde::Serialize;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+use utoipa::ToSchema;
+
+use crate::util::{config, entities};
+
+/// Maximum weighted length of a post
+pub const MAX_WEIGHTED_LENGTH: usize = 280;
+
+// Every URL counts as this many characters, whatever its actual length
+const URL_WEIGHT: usize = 23;
+
+// Code point ranges that count as a single character, everything else counts double
+const LIGHT_RANGES: &[(u32, u32)] = &[
+    (0x0000, 0x10FF), // Latin, Greek, Cyrillic, Hebrew, Arabic, Indic scripts...
+    (0x2000, 0x200D), // Spaces and zero width characters
+    (0x2010, 0x201F), // Dashes and quotation marks
+    (0x2032, 0x2037), // Primes
+];
+
+/// Reasons post content can be rejected
+#[derive(Serialize, Debug, ToSchema)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum ContentError {
+    /// The content is empty or only whitespace
+    Empty,
+    /// The content is longer than allowed
+    TooLong {
+        /// Weighted length of the content
+        weighted_length: usize,
+        /// Maximum weighted length allowed
+        max_length: usize,
+    },
+    /// The content mentions more users than allowed
+    TooManyMentions {
+        /// Number of distinct users mentioned
+        mention_count: usize,
+        /// Maximum number of mentions allowed
+        max_mentions: usize,
+    },
+    /// The content contains a character that is not allowed
+    DisallowedCharacter {
+        /// Position of the character, counted in grapheme clusters
+        position: usize,
+        /// The character, as a `U+XXXX` code point
+        code_point: String,
+    },
+}
+
+/// Body of a 422 response for rejected content
+#[derive(Serialize, Debug, ToSchema)]
+#[schema(example = json!({
+    "error": "Invalid post content",
+    "details": [{ "code": "too_long", "weighted_length": 291, "max_length": 280 }]
+}))]
+pub struct ContentErrorResponse {
+    /// Summary of the error
+    pub error: &'static str,
+    /// Everything wrong with the content
+    pub details: Vec<ContentError>,
+}
+
+impl ContentErrorResponse {
+    /// Builds the response for the given errors
+    pub fn new(details: Vec<ContentError>) -> Self {
+        Self {
+            error: "Invalid post content",
+            details,
+        }
+    }
+}
+
+/// Validates post content, returning it normalized to NFC
+pub fn validate(content: &str) -> Result<String, Vec<ContentError>> {
+    let normalized: String = content.replace("\r\n", "\n").nfc().collect();
+    let mut errors = Vec::new();
+
+    if normalized.trim().is_empty() {
+        errors.push(ContentError::Empty);
+    }
+
+    for (position, grapheme) in normalized.graphemes(true).enumerate() {
+        if let Some(disallowed) = grapheme.chars().find(|c| is_disallowed(*c)) {
+            errors.push(ContentError::DisallowedCharacter {
+                position,
+                code_point: format!("U+{:04X}", disallowed as u32),
+            });
+        }
+    }
+
+    let weighted_length = weighted_length(&normalized);
+    if weighted_length > MAX_WEIGHTED_LENGTH {
+        errors.push(ContentError::TooLong {
+            weighted_length,
+            max_length: MAX_WEIGHTED_LENGTH,
+        });
+    }
+
+    // Limit mentions to keep posts from being used to spam users
+    let max_mentions = config::env_or("POST_MAX_MENTIONS", 10);
+    let mention_count = entities::mentions(&normalized).len();
+    if mention_count > max_mentions {
+        errors.push(ContentError::TooManyMentions {
+            mention_count,
+            max_mentions,
+        });
+    }
+
+    if errors.is_empty() {
+        Ok(normalized)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Length of `text` as counted against the post length limit
+///
+/// Each grapheme cluster counts as 1 or 2 depending on its script (CJK and emoji
+/// count double) and each URL counts as a fixed 23.
+pub fn weighted_length(text: &str) -> usize {
+    let mut length = 0;
+    let mut position = 0;
+
+    for url in entities::url_ranges(text) {
+        length += graphemes_weight(&text[position..url.start]) + URL_WEIGHT;
+        position = url.end;
+    }
+
+    length + graphemes_weight(&text[position..])
+}
+
+fn graphemes_weight(text: &str) -> usize {
+    text.graphemes(true)
+        .map(|grapheme| {
+            let first = grapheme.chars().next().map_or(0, |c| c as u32);
+            let is_light = LIGHT_RANGES
+                .iter()
+                .any(|(start, end)| (*start..=*end).contains(&first));
+            if is_light { 1 } else { 2 }
+        })
+        .sum()
+}
+
+// Control characters other than newlines and tabs, and bidirectional overrides
+fn is_disallowed(c: char) -> bool {
+    (c.is_control() && c != '\n' && c != '\t')
+        || ('\u{202A}'..='\u{202E}').contains(&c)
+        || ('\u{2066}'..='\u{2069}').contains(&c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weighted_length_counts_latin_as_one() {
+        assert_eq!(weighted_length("hello"), 5);
+        assert_eq!(weighted_length("héllo wörld"), 11);
+    }
+
+    #[test]
+    fn weighted_length_counts_cjk_as_two() {
+        assert_eq!(weighted_length("日本語"), 6);
+        assert_eq!(weighted_length("a日b"), 4);
+    }
+
+    #[test]
+    fn weighted_length_counts_emoji_sequences_once() {
+        // Family: man, woman, girl joined by zero width joiners
+        assert_eq!(
+            weighted_length("\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}"),
+            2
+        );
+        // Thumbs up with a skin tone modifier
+        assert_eq!(weighted_length("\u{1F44D}\u{1F3FD}"), 2);
+        // Flag made of two regional indicators
+        assert_eq!(weighted_length("\u{1F1EB}\u{1F1F7}"), 2);
+    }
+
+    #[test]
+    fn weighted_length_counts_urls_as_23() {
+        assert_eq!(weighted_length("https://example.com"), URL_WEIGHT);
+        assert_eq!(
+            weighted_length("see https://example.com/a/very/long/path?with=query ok"),
+            4 + URL_WEIGHT + 3
+        );
+    }
+
+    #[test]
+    fn validate_accepts_the_maximum_length() {
+        assert!(validate(&"a".repeat(MAX_WEIGHTED_LENGTH)).is_ok());
+        assert!(validate(&"日".repeat(MAX_WEIGHTED_LENGTH / 2)).is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_one_over_the_maximum_length() {
+        let errors = validate(&"a".repeat(MAX_WEIGHTED_LENGTH + 1)).unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [ContentError::TooLong {
+                weighted_length: 281,
+                max_length: 280
+            }]
+        ));
+
+        let errors = validate(&format!("{}日", "a".repeat(MAX_WEIGHTED_LENGTH - 1))).unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [ContentError::TooLong {
+                weighted_length: 281,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn validate_rejects_empty_content() {
+        assert!(matches!(
+            validate("").unwrap_err().as_slice(),
+            [ContentError::Empty]
+        ));
+        assert!(matches!(
+            validate(" \n\t ").unwrap_err().as_slice(),
+            [ContentError::Empty]
+        ));
+    }
+
+    #[test]
+    fn validate_normalizes_to_nfc() {
+        // "e" followed by a combining acute accent becomes a single "é"
+        assert_eq!(validate("cafe\u{301}").unwrap(), "caf\u{E9}");
+        assert_eq!(validate("a\r\nb").unwrap(), "a\nb");
+    }
+
+    #[test]
+    fn validate_rejects_bidi_overrides() {
+        let errors = validate("abc\u{202E}def").unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [ContentError::DisallowedCharacter { position: 3, code_point }] if code_point == "U+202E"
+        ));
+
+        let errors = validate("\u{2066}isolated").unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [ContentError::DisallowedCharacter { position: 0, code_point }] if code_point == "U+2066"
+        ));
+    }
+
+    #[test]
+    fn validate_rejects_control_characters_but_not_newlines_or_tabs() {
+        let errors = validate("a\u{7}b").unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [ContentError::DisallowedCharacter { position: 1, code_point }] if code_point == "U+0007"
+        ));
+
+        assert!(validate("line\nnext\tcolumn").is_ok());
+    }
+
+    #[test]
+    fn validate_reports_positions_in_graphemes() {
+        // The flag before the control character is a single grapheme
+        let errors = validate("\u{1F1EB}\u{1F1F7}\u{0}").unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [ContentError::DisallowedCharacter { position: 1, .. }]
+        ));
+    }
+
+    #[test]
+    fn validate_limits_distinct_mentions() {
+        let mentions = (0..10).map(|i| format!("@user{}", i)).collect::<Vec<_>>();
+        assert!(validate(&mentions.join(" ")).is_ok());
+
+        // Mentioning the same user again does not count
+        assert!(validate(&format!("{} @user0", mentions.join(" "))).is_ok());
+
+        let errors = validate(&format!("{} @user10", mentions.join(" "))).unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [ContentError::TooManyMentions {
+                mention_count: 11,
+                max_mentions: 10
+            }]
+        ));
+    }
+
+    #[test]
+    fn validate_reports_every_error() {
+        let content = format!("{}\u{202E}", "a".repeat(MAX_WEIGHTED_LENGTH));
+        let errors = validate(&content).unwrap_err();
+        assert_eq!(errors.len(), 2);
+    }
+}
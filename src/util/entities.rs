@@ -0,0 +1,383 @@
+use std::ops::Range;
+
+use unicode_normalization::UnicodeNormalization;
+
+// Characters other than letters, marks and digits allowed inside a hashtag
+const HASHTAG_EXTRA_CHARACTERS: &[char] = &[
+    '_', '\u{200C}', '\u{200D}', '\u{00B7}', '\u{30FB}', '\u{0F0B}',
+];
+
+// Characters that prevent an `@` right after them from starting a mention
+const MENTION_BLOCKING_CHARACTERS: &[char] = &['_', '!', '@', '#', '$', '%', '&', '*'];
+
+// Characters that end a URL when they are its last character
+const URL_TRAILING_PUNCTUATION: &[char] = &['.', ',', '!', '?', ':', ';', ')', '"', '\''];
+
+/// Finds the URLs in `text`, returned as byte ranges
+///
+/// A URL starts with `http://`, `https://` or `www.` at the beginning of a word and
+/// runs until the next whitespace, minus any trailing punctuation.
+pub fn url_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut search_from = 0;
+
+    while let Some(offset) = find_url_start(&text[search_from..]) {
+        let start = search_from + offset;
+        let mut end = text[start..]
+            .find(char::is_whitespace)
+            .map_or(text.len(), |length| start + length);
+
+        while let Some(last) = text[start..end].chars().next_back() {
+            if !URL_TRAILING_PUNCTUATION.contains(&last) {
+                break;
+            }
+            end -= last.len_utf8();
+        }
+
+        if has_host(&text[start..end]) {
+            ranges.push(start..end);
+        }
+        search_from = end.max(start + 1);
+    }
+
+    ranges
+}
+
+// Finds the next URL scheme or `www.` prefix that starts a word
+fn find_url_start(text: &str) -> Option<usize> {
+    let lowercase = text.to_ascii_lowercase();
+    let mut search_from = 0;
+
+    loop {
+        let offset = ["https://", "http://", "www."]
+            .iter()
+            .filter_map(|prefix| lowercase[search_from..].find(prefix))
+            .min()?;
+        let start = search_from + offset;
+
+        let at_word_start = text[..start]
+            .chars()
+            .next_back()
+            .is_none_or(|previous| !previous.is_alphanumeric() && previous != '/');
+        if at_word_start {
+            return Some(start);
+        }
+        search_from = start + 1;
+    }
+}
+
+// A URL needs at least one character after its scheme or prefix
+fn has_host(url: &str) -> bool {
+    let lowercase = url.to_ascii_lowercase();
+    let rest = ["https://", "http://", "www."]
+        .iter()
+        .find_map(|prefix| lowercase.strip_prefix(prefix))
+        .unwrap_or_default();
+
+    rest.chars().next().is_some_and(char::is_alphanumeric)
+}
+
+/// Finds the hashtags in `text`, returned as byte ranges including the leading `#`
+///
+/// Follows Twitter's rules: a hashtag starts with `#` or `＃` that is not preceded by a
+/// letter, digit or `&`, is made of letters, marks, digits and underscores, contains at
+/// least one non-digit, is not followed by another `#` or `://`, and is not part of a URL.
+pub fn hashtag_ranges(text: &str) -> Vec<Range<usize>> {
+    let urls = url_ranges(text);
+    let mut ranges = Vec::new();
+    let mut previous: Option<char> = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let preceded_by_word = previous.is_some_and(|p| is_hashtag_character(p) || p == '&');
+        previous = Some(c);
+        if !matches!(c, '#' | '＃') || preceded_by_word {
+            continue;
+        }
+
+        let mut end = start + c.len_utf8();
+        while let Some(&(index, next)) = chars.peek() {
+            if !is_hashtag_character(next) {
+                break;
+            }
+            end = index + next.len_utf8();
+            previous = Some(next);
+            chars.next();
+        }
+
+        let tag = &text[start + c.len_utf8()..end];
+        let rest = &text[end..];
+        let is_valid = tag.chars().any(|c| !c.is_numeric() && c != '_')
+            && !rest.starts_with(['#', '＃'])
+            && !rest.starts_with("://")
+            && !urls.iter().any(|url| url.contains(&start));
+        if is_valid {
+            ranges.push(start..end);
+        }
+    }
+
+    ranges
+}
+
+/// Normalizes a hashtag for indexing (NFKC, lowercase), without its leading `#`
+pub fn normalize_hashtag(hashtag: &str) -> String {
+    hashtag
+        .trim_start_matches(['#', '＃'])
+        .nfkc()
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// The normalized, deduplicated hashtags in `text`, in order of appearance
+pub fn hashtags(text: &str) -> Vec<String> {
+    let mut hashtags: Vec<String> = Vec::new();
+    for range in hashtag_ranges(text) {
+        let hashtag = normalize_hashtag(&text[range]);
+        if !hashtags.contains(&hashtag) {
+            hashtags.push(hashtag);
+        }
+    }
+    hashtags
+}
+
+fn is_hashtag_character(c: char) -> bool {
+    c.is_alphanumeric()
+        || HASHTAG_EXTRA_CHARACTERS.contains(&c)
+        || ('\u{0300}'..='\u{036F}').contains(&c)
+}
+
+/// Finds the mentions in `text`, returned as byte ranges including the leading `@`
+///
+/// A mention is `@` or `＠` followed by letters, digits and underscores. It is not
+/// preceded by a letter, digit or one of `_!@#$%&*`, is not followed by another `@`
+/// (as in an email address) or `://`, and is not part of a URL.
+pub fn mention_ranges(text: &str) -> Vec<Range<usize>> {
+    let urls = url_ranges(text);
+    let mut ranges = Vec::new();
+    let mut previous: Option<char> = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let blocked = previous
+            .is_some_and(|p| p.is_alphanumeric() || MENTION_BLOCKING_CHARACTERS.contains(&p));
+        previous = Some(c);
+        if !matches!(c, '@' | '＠') || blocked {
+            continue;
+        }
+
+        let mut end = start + c.len_utf8();
+        while let Some(&(index, next)) = chars.peek() {
+            if !is_username_character(next) {
+                break;
+            }
+            end = index + next.len_utf8();
+            previous = Some(next);
+            chars.next();
+        }
+
+        let rest = &text[end..];
+        let is_valid = end > start + c.len_utf8()
+            && !rest.starts_with(['@', '＠'])
+            && !rest.starts_with("://")
+            && !urls.iter().any(|url| url.contains(&start));
+        if is_valid {
+            ranges.push(start..end);
+        }
+    }
+
+    ranges
+}
+
+/// The deduplicated usernames mentioned in `text`, without the leading `@`
+pub fn mentions(text: &str) -> Vec<String> {
+    let mut usernames: Vec<String> = Vec::new();
+    for range in mention_ranges(text) {
+        let username = text[range].trim_start_matches(['@', '＠']).to_string();
+        if !usernames
+            .iter()
+            .any(|known| known.eq_ignore_ascii_case(&username))
+        {
+            usernames.push(username);
+        }
+    }
+    usernames
+}
+
+fn is_username_character(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Kinds of entities found in post content
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntityKind {
+    Url,
+    Hashtag,
+    Mention,
+}
+
+/// Finds the URLs, hashtags and mentions in `text`, ordered by position
+///
+/// This is the single parser behind the entities returned to clients, so that every
+/// client highlights exactly the same ranges.
+pub fn parse(text: &str) -> Vec<(EntityKind, Range<usize>)> {
+    let mut entities = url_ranges(text)
+        .into_iter()
+        .map(|range| (EntityKind::Url, range))
+        .chain(
+            hashtag_ranges(text)
+                .into_iter()
+                .map(|range| (EntityKind::Hashtag, range)),
+        )
+        .chain(
+            mention_ranges(text)
+                .into_iter()
+                .map(|range| (EntityKind::Mention, range)),
+        )
+        .collect::<Vec<_>>();
+
+    entities.sort_by_key(|(_, range)| range.start);
+    entities
+}
+
+/// Converts a byte offset in `text` to an offset in UTF-16 code units
+pub fn utf16_offset(text: &str, byte_offset: usize) -> usize {
+    text[..byte_offset].encode_utf16().count()
+}
+
+/// The URL a link in post content points to, adding `https://` to `www.` links
+pub fn expand_url(url: &str) -> String {
+    if url.to_ascii_lowercase().starts_with("www.") {
+        format!("https://{}", url)
+    } else {
+        url.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matched(text: &str, ranges: Vec<Range<usize>>) -> Vec<&str> {
+        ranges.into_iter().map(|range| &text[range]).collect()
+    }
+
+    #[test]
+    fn hashtag_ranges_follow_twitter_rules() {
+        let cases: &[(&str, &[&str])] = &[
+            ("#rust", &["#rust"]),
+            ("hello #rust and #go", &["#rust", "#go"]),
+            ("#1", &[]),
+            ("#2024", &[]),
+            ("#___", &[]),
+            ("#1st", &["#1st"]),
+            ("#_private", &["#_private"]),
+            ("#tag_with_underscore", &["#tag_with_underscore"]),
+            ("a#b", &[]),
+            ("1#b", &[]),
+            ("&#39;", &[]),
+            ("(#tag)", &["#tag"]),
+            ("#tag.", &["#tag"]),
+            ("#tag#other", &[]),
+            ("#tag＃other", &[]),
+            ("#", &[]),
+            ("# tag", &[]),
+            ("＃全角", &["＃全角"]),
+            ("日本語の#ハッシュタグ", &[]),
+            ("日本語 #ハッシュタグ", &["#ハッシュタグ"]),
+            ("#café", &["#café"]),
+            ("#cafe\u{301}", &["#cafe\u{301}"]),
+            ("#http://example.com", &[]),
+            ("https://example.com/#anchor", &[]),
+            ("https://example.com/ #anchor", &["#anchor"]),
+        ];
+
+        for (text, expected) in cases {
+            assert_eq!(
+                matched(text, hashtag_ranges(text)),
+                *expected,
+                "hashtags in {:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn normalize_hashtag_folds_case_and_width() {
+        let cases = [
+            ("#Rust", "rust"),
+            ("＃Rust", "rust"),
+            ("#ＲＵＳＴ", "rust"),
+            ("#cafe\u{301}", "caf\u{E9}"),
+            ("#tag_with_underscore", "tag_with_underscore"),
+            ("#ハッシュタグ", "ハッシュタグ"),
+        ];
+
+        for (hashtag, expected) in cases {
+            assert_eq!(normalize_hashtag(hashtag), expected, "{:?}", hashtag);
+        }
+    }
+
+    #[test]
+    fn hashtags_are_deduplicated_after_normalization() {
+        assert_eq!(hashtags("#Rust #rust ＃RUST #go"), ["rust", "go"]);
+    }
+
+    #[test]
+    fn mention_ranges_skip_emails_and_urls() {
+        let cases: &[(&str, &[&str])] = &[
+            ("@user", &["@user"]),
+            ("hi @alice and @bob_2", &["@alice", "@bob_2"]),
+            ("@user.", &["@user"]),
+            ("@user, hello", &["@user"]),
+            ("(@user)", &["@user"]),
+            ("＠user", &["＠user"]),
+            ("email@host", &[]),
+            ("email@host.com", &[]),
+            ("me@user@host", &[]),
+            ("@user@host", &[]),
+            ("_@user", &[]),
+            ("!@user", &[]),
+            ("#@user", &[]),
+            ("@", &[]),
+            ("@ user", &[]),
+            ("@@user", &[]),
+            ("@user://example", &[]),
+            ("https://example.com/@user", &[]),
+            ("日本@user", &[]),
+            ("日本 @user", &["@user"]),
+        ];
+
+        for (text, expected) in cases {
+            assert_eq!(
+                matched(text, mention_ranges(text)),
+                *expected,
+                "mentions in {:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn mentions_are_deduplicated_ignoring_case() {
+        assert_eq!(mentions("@Alice @alice ＠ALICE @bob"), ["Alice", "bob"]);
+    }
+
+    #[test]
+    fn utf16_offset_counts_astral_characters_as_two_units() {
+        let text = "😀 #tag";
+        let range = &hashtag_ranges(text)[0];
+        assert_eq!(range.clone(), 5..9);
+        assert_eq!(utf16_offset(text, range.start), 3);
+        assert_eq!(utf16_offset(text, range.end), 7);
+
+        // Family emoji: three astral code points joined by two zero width joiners
+        let text = "👨\u{200D}👩\u{200D}👧 @user";
+        let range = &mention_ranges(text)[0];
+        assert_eq!(utf16_offset(text, range.start), 9);
+        assert_eq!(utf16_offset(text, range.end), 14);
+
+        // Characters of the Basic Multilingual Plane are one unit, however many bytes
+        assert_eq!(utf16_offset("日本 #tag", "日本 ".len()), 3);
+        assert_eq!(utf16_offset("", 0), 0);
+    }
+}
@@ -0,0 +1,382 @@
+use std::io::Cursor;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, Limits};
+
+use crate::util::blurhash;
+
+// Resized copies made of each image, by name and longest side in pixels. A copy is
+// only made when the image is larger than that.
+const VARIANTS: &[(&str, u32)] = &[
+    ("thumb", 150),
+    ("small", 680),
+    ("medium", 1200),
+    ("large", 2048),
+];
+
+// Largest width or height accepted, to keep decoding memory bounded
+const MAX_DIMENSION: u32 = 8192;
+
+// Quality used when encoding JPEG copies
+const JPEG_QUALITY: u8 = 85;
+
+// Quality used when re-encoding JPEG originals, high as they are shown full size
+const ORIGINAL_JPEG_QUALITY: u8 = 92;
+
+// Longest side of the copy placeholders are computed from. They only keep the broad
+// shapes and colours of the image.
+const PLACEHOLDER_SIZE: u32 = 64;
+
+/// An image format accepted for upload
+#[derive(Clone, Copy, Debug)]
+pub struct Format {
+    /// Format used to decode the image
+    pub format: ImageFormat,
+    /// MIME type of the format
+    pub content_type: &'static str,
+    /// File extension used when storing the image
+    pub extension: &'static str,
+}
+
+const JPEG: Format = Format {
+    format: ImageFormat::Jpeg,
+    content_type: "image/jpeg",
+    extension: "jpg",
+};
+
+const PNG: Format = Format {
+    format: ImageFormat::Png,
+    content_type: "image/png",
+    extension: "png",
+};
+
+const GIF: Format = Format {
+    format: ImageFormat::Gif,
+    content_type: "image/gif",
+    extension: "gif",
+};
+
+const WEBP: Format = Format {
+    format: ImageFormat::WebP,
+    content_type: "image/webp",
+    extension: "webp",
+};
+
+/// Detects the format of an image from its first bytes, whatever its name or the
+/// content type the client claims
+pub fn detect_format(bytes: &[u8]) -> Option<Format> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(JPEG)
+    } else if bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some(PNG)
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some(GIF)
+    } else if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+        Some(WEBP)
+    } else {
+        None
+    }
+}
+
+/// A resized copy of an image
+pub struct Variant {
+    /// Name of the variant, e.g. `small`
+    pub name: &'static str,
+    pub width: u32,
+    pub height: u32,
+    /// Encoded image
+    pub bytes: Vec<u8>,
+    /// Format of the encoded image
+    pub format: Format,
+}
+
+/// An uploaded image, decoded, stripped of its metadata and resized
+pub struct ProcessedImage {
+    pub width: u32,
+    pub height: u32,
+    /// The image re-encoded without any metadata, stored in place of the upload
+    pub bytes: Vec<u8>,
+    /// Format of the re-encoded image
+    pub format: Format,
+    /// BlurHash of the image, to show while it loads
+    pub blurhash: String,
+    /// Most common colour of the image, as `#rrggbb`
+    pub dominant_color: String,
+    /// Resized copies, smallest first
+    pub variants: Vec<Variant>,
+}
+
+/// Decodes an image, re-encodes it without its metadata and makes its resized copies
+/// and placeholders
+///
+/// Only the pixels are kept, so EXIF and XMP data such as GPS coordinates never reach
+/// the media store. Animated images keep their first frame only.
+pub fn process(bytes: &[u8], format: Format) -> Result<ProcessedImage, &'static str> {
+    let image = decode(bytes, format)?;
+
+    // JPEG images stay JPEG, everything else becomes PNG to keep transparency
+    let stored_format = match format.format {
+        ImageFormat::Jpeg => JPEG,
+        _ => PNG,
+    };
+    let original = encode(&image, stored_format, ORIGINAL_JPEG_QUALITY)?;
+
+    let small = image.thumbnail(PLACEHOLDER_SIZE, PLACEHOLDER_SIZE);
+    let (x_components, y_components) = if small.width() >= small.height() {
+        (4, 3)
+    } else {
+        (3, 4)
+    };
+
+    let mut variants = Vec::new();
+    for (name, size) in VARIANTS {
+        if image.width().max(image.height()) <= *size {
+            break;
+        }
+
+        let resized = image.resize(*size, *size, FilterType::CatmullRom);
+        variants.push(Variant {
+            name,
+            width: resized.width(),
+            height: resized.height(),
+            bytes: encode(&resized, stored_format, JPEG_QUALITY)?,
+            format: stored_format,
+        });
+    }
+
+    Ok(ProcessedImage {
+        width: image.width(),
+        height: image.height(),
+        bytes: original,
+        format: stored_format,
+        blurhash: blurhash::encode(&small.to_rgb8(), x_components, y_components),
+        dominant_color: dominant_color(&small),
+        variants,
+    })
+}
+
+fn decode(bytes: &[u8], format: Format) -> Result<DynamicImage, &'static str> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+
+    let mut reader = ImageReader::with_format(Cursor::new(bytes), format.format);
+    reader.limits(limits);
+    let mut decoder = reader
+        .into_decoder()
+        .map_err(|_| "Image could not be decoded")?;
+
+    // The orientation is part of the metadata that is dropped, so the pixels are
+    // turned the way it says first
+    let orientation = decoder
+        .orientation()
+        .map_err(|_| "Image could not be decoded")?;
+    let mut image =
+        DynamicImage::from_decoder(decoder).map_err(|_| "Image could not be decoded")?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+fn encode(image: &DynamicImage, format: Format, quality: u8) -> Result<Vec<u8>, &'static str> {
+    let mut bytes = Vec::new();
+    let result = match format.format {
+        ImageFormat::Jpeg => JpegEncoder::new_with_quality(&mut bytes, quality)
+            .encode_image(&DynamicImage::ImageRgb8(image.to_rgb8())),
+        _ => image.write_to(&mut Cursor::new(&mut bytes), format.format),
+    };
+    result.map_err(|_| "Image could not be encoded")?;
+    Ok(bytes)
+}
+
+// Most common colour of an image, counting similar colours together and leaving out
+// mostly transparent pixels
+fn dominant_color(image: &DynamicImage) -> String {
+    // Pixel count and sum of each channel, for 16 levels of each channel
+    let mut buckets = vec![(0u32, [0u32; 3]); 16 * 16 * 16];
+    for pixel in image.to_rgba8().pixels() {
+        let [r, g, b, a] = pixel.0;
+        if a < 128 {
+            continue;
+        }
+        let bucket =
+            &mut buckets[(r as usize >> 4) << 8 | (g as usize >> 4) << 4 | b as usize >> 4];
+        bucket.0 += 1;
+        for (sum, channel) in bucket.1.iter_mut().zip([r, g, b]) {
+            *sum += channel as u32;
+        }
+    }
+
+    // The first of equally common colours, and black for fully transparent images
+    let (count, sums) = buckets
+        .into_iter()
+        .rev()
+        .max_by_key(|(count, _)| *count)
+        .filter(|(count, _)| *count > 0)
+        .unwrap_or((1, [0; 3]));
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        sums[0] / count,
+        sums[1] / count,
+        sums[2] / count
+    )
+}
+
+/// MIME type of a stored image, from the extension of its key
+pub fn content_type_of(key: &str) -> Option<&'static str> {
+    let extension = key.rsplit_once('.')?.1;
+    [JPEG, PNG, GIF, WEBP]
+        .iter()
+        .find(|format| format.extension == extension)
+        .map(|format| format.content_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+    // EXIF data saying the image must be turned 90° clockwise to be upright, with a
+    // GPS latitude reference
+    fn exif_segment() -> Vec<u8> {
+        let mut tiff: Vec<u8> = b"II*\0".to_vec();
+        tiff.extend(8u32.to_le_bytes());
+        // IFD0: orientation and a pointer to the GPS IFD, at offset 38
+        tiff.extend(2u16.to_le_bytes());
+        tiff.extend([0x12, 0x01, 3, 0, 1, 0, 0, 0, 6, 0, 0, 0]);
+        tiff.extend([0x25, 0x88, 4, 0, 1, 0, 0, 0, 38, 0, 0, 0]);
+        tiff.extend(0u32.to_le_bytes());
+        // GPS IFD: latitude reference "N"
+        tiff.extend(1u16.to_le_bytes());
+        tiff.extend([0x01, 0x00, 2, 0, 2, 0, 0, 0, b'N', 0, 0, 0]);
+        tiff.extend(0u32.to_le_bytes());
+
+        let mut data = b"Exif\0\0".to_vec();
+        data.extend(tiff);
+        let mut segment = vec![0xFF, 0xE1];
+        segment.extend(((data.len() + 2) as u16).to_be_bytes());
+        segment.extend(data);
+        segment
+    }
+
+    // A 400x200 JPEG, red on the left and blue on the right, with the EXIF data
+    fn sideways_jpeg() -> Vec<u8> {
+        let image = RgbImage::from_fn(400, 200, |x, _| {
+            if x < 200 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([0, 0, 255])
+            }
+        });
+        let mut jpeg = Vec::new();
+        JpegEncoder::new_with_quality(&mut jpeg, 90)
+            .encode_image(&image)
+            .unwrap();
+
+        // Right after the start of image marker
+        let mut with_exif = jpeg[..2].to_vec();
+        with_exif.extend(exif_segment());
+        with_exif.extend(&jpeg[2..]);
+        with_exif
+    }
+
+    // Markers of the segments before the image data of a JPEG
+    fn jpeg_markers(jpeg: &[u8]) -> Vec<u8> {
+        let mut markers = Vec::new();
+        let mut at = 2;
+        while at + 4 <= jpeg.len() && jpeg[at] == 0xFF {
+            let marker = jpeg[at + 1];
+            markers.push(marker);
+            if marker == 0xDA {
+                break;
+            }
+            at += 2 + u16::from_be_bytes([jpeg[at + 2], jpeg[at + 3]]) as usize;
+        }
+        markers
+    }
+
+    fn pixel_at(jpeg: &[u8], x: u32, y: u32) -> [u8; 3] {
+        image::load_from_memory_with_format(jpeg, ImageFormat::Jpeg)
+            .unwrap()
+            .to_rgb8()
+            .get_pixel(x, y)
+            .0
+    }
+
+    fn is_close(actual: [u8; 3], expected: [u8; 3]) -> bool {
+        actual
+            .iter()
+            .zip(expected)
+            .all(|(actual, expected)| actual.abs_diff(expected) < 40)
+    }
+
+    #[test]
+    fn process_turns_images_upright_and_strips_exif() {
+        let upload = sideways_jpeg();
+        assert!(jpeg_markers(&upload).contains(&0xE1));
+
+        let processed = process(&upload, detect_format(&upload).unwrap()).unwrap();
+        assert_eq!(processed.format.content_type, "image/jpeg");
+        assert_eq!((processed.width, processed.height), (200, 400));
+        assert_eq!(processed.variants.len(), 1);
+        assert_eq!(processed.variants[0].name, "thumb");
+        assert_eq!(
+            (processed.variants[0].width, processed.variants[0].height),
+            (75, 150)
+        );
+
+        let files = std::iter::once((&processed.bytes, 200, 400)).chain(
+            processed
+                .variants
+                .iter()
+                .map(|variant| (&variant.bytes, variant.width, variant.height)),
+        );
+        for (bytes, width, height) in files {
+            let markers = jpeg_markers(bytes);
+            assert!(markers.contains(&0xDA), "{:x?}", markers);
+            assert!(!markers.contains(&0xE1), "{:x?}", markers);
+            assert!(!bytes.windows(6).any(|window| window == b"Exif\0\0"));
+
+            // Turned clockwise, the left of the image is now at the top
+            assert!(is_close(
+                pixel_at(bytes, width / 2, height / 8),
+                [255, 0, 0]
+            ));
+            assert!(is_close(
+                pixel_at(bytes, width / 2, height * 7 / 8),
+                [0, 0, 255]
+            ));
+        }
+    }
+
+    #[test]
+    fn process_refuses_what_cannot_be_decoded() {
+        let mut truncated = sideways_jpeg();
+        truncated.truncate(100);
+        assert!(process(&truncated, JPEG).is_err());
+        assert!(process(b"not an image", PNG).is_err());
+    }
+
+    #[test]
+    fn dominant_color_of_a_solid_image_is_that_colour() {
+        let image = DynamicImage::ImageRgb8(RgbImage::from_pixel(32, 32, Rgb([0x12, 0x34, 0x56])));
+        assert_eq!(dominant_color(&image), "#123456");
+    }
+
+    #[test]
+    fn dominant_color_is_the_most_common_opaque_colour() {
+        // Mostly transparent white, then more green than red
+        let image = RgbaImage::from_fn(10, 10, |x, _| match x {
+            0..5 => Rgba([255, 255, 255, 0]),
+            5..8 => Rgba([0, 200, 0, 255]),
+            _ => Rgba([200, 0, 0, 255]),
+        });
+        assert_eq!(dominant_color(&DynamicImage::ImageRgba8(image)), "#00c800");
+
+        let transparent = RgbaImage::from_pixel(4, 4, Rgba([255, 255, 255, 0]));
+        assert_eq!(
+            dominant_color(&DynamicImage::ImageRgba8(transparent)),
+            "#000000"
+        );
+    }
+}
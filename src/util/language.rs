@@ -0,0 +1,81 @@
+use isolang::Language;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::util::entities;
+
+// Detections less confident than this are dropped. Posts are too short for the
+// detector's own reliability check, which wants whole paragraphs.
+const MIN_CONFIDENCE: f64 = 0.25;
+
+/// Maximum number of languages a filter or a user's preferences can list
+pub const MAX_LANGUAGES: usize = 10;
+
+/// Query parameters for endpoints that can be filtered by language
+#[derive(Deserialize, IntoParams)]
+pub struct LanguageFilter {
+    /// Comma separated ISO 639-1 codes of the languages to include, e.g. `en,fr`.
+    /// Posts whose language is unknown are left out.
+    pub lang: Option<String>,
+}
+
+impl LanguageFilter {
+    /// Languages to include, if the request filters by language
+    pub fn languages(&self) -> Result<Option<Vec<String>>, &'static str> {
+        self.lang
+            .as_deref()
+            .map(|lang| normalize_all(lang.split(',')))
+            .transpose()
+    }
+}
+
+/// Detects the language of post content, as an ISO 639-1 code
+///
+/// URLs, hashtags and mentions are left out since they say little about the
+/// language. Returns `None` when the content is too short or too ambiguous to tell.
+pub fn detect(content: &str) -> Option<String> {
+    let mut text = String::with_capacity(content.len());
+    let mut start = 0;
+    for (_, range) in entities::parse(content) {
+        text.push_str(&content[start..range.start]);
+        text.push(' ');
+        start = range.end;
+    }
+    text.push_str(&content[start..]);
+
+    let info = whatlang::detect(&text)?;
+    if info.confidence() < MIN_CONFIDENCE {
+        return None;
+    }
+
+    Language::from_639_3(info.lang().code())
+        .and_then(|language| language.to_639_1())
+        .map(str::to_string)
+}
+
+/// Checks that `code` is an ISO 639-1 language code, returning it in lowercase
+pub fn normalize(code: &str) -> Result<String, &'static str> {
+    let code = code.trim().to_ascii_lowercase();
+    match Language::from_639_1(&code) {
+        Some(_) => Ok(code),
+        None => Err("Unknown language code"),
+    }
+}
+
+/// Normalizes a list of language codes, dropping duplicates
+pub fn normalize_all<'a>(
+    codes: impl IntoIterator<Item = &'a str>,
+) -> Result<Vec<String>, &'static str> {
+    let mut languages: Vec<String> = Vec::new();
+    for code in codes {
+        let code = normalize(code)?;
+        if !languages.contains(&code) {
+            languages.push(code);
+        }
+    }
+
+    if languages.len() > MAX_LANGUAGES {
+        return Err("Too many languages");
+    }
+    Ok(languages)
+}
@@ -1,3 +1,14 @@
 pub mod api_doc;
+pub mod blurhash;
+pub mod config;
+pub mod content;
 pub mod db;
-pub mod auth;
\ No newline at end of file
+pub mod entities;
+pub mod images;
+pub mod language;
+pub mod auth;
+pub mod pagination;
+#[cfg(test)]
+pub mod test_server;
+pub mod unfurl;
+pub mod views;
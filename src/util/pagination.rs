@@ -0,0 +1,26 @@
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+const DEFAULT_LIMIT: i64 = 20;
+const MAX_LIMIT: i64 = 100;
+
+/// Query parameters for paginated endpoints
+#[derive(Deserialize, IntoParams)]
+pub struct Pagination {
+    /// Maximum number of items to return (default 20, max 100)
+    pub limit: Option<i64>,
+    /// Number of items to skip
+    pub offset: Option<i64>,
+}
+
+impl Pagination {
+    /// Number of items to return, clamped to a sane range
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+
+    /// Number of items to skip, never negative
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+}
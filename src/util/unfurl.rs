@@ -0,0 +1,700 @@
+use std::io::{self, Read};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs};
+use std::time::Duration;
+
+use url::Url;
+
+use crate::util::config;
+
+// Redirects followed before giving up on a URL
+const MAX_REDIRECTS: u32 = 3;
+
+// Longest title and description kept, in characters
+const MAX_TITLE_LENGTH: usize = 200;
+const MAX_DESCRIPTION_LENGTH: usize = 500;
+
+/// Metadata of a web page, as shown in its preview card
+#[derive(Debug)]
+pub struct PageCard {
+    /// `summary` or `summary_large_image`, as in Twitter Cards
+    pub card_type: String,
+    pub title: String,
+    pub description: Option<String>,
+    /// Absolute URL of the preview image
+    pub image_url: Option<String>,
+    pub site_name: Option<String>,
+}
+
+/// Why a page could not be fetched
+#[derive(Debug, PartialEq)]
+pub enum FetchError {
+    /// Fetching the page again will not help, e.g. for an unsupported URL, a
+    /// private address or a missing page
+    Permanent(&'static str),
+    /// Fetching the page may work later, e.g. after a timeout, a DNS failure or a
+    /// server error
+    Transient(&'static str),
+}
+
+/// Fetches web pages for previews, refusing to connect anywhere but the public internet
+#[derive(Clone)]
+pub struct Unfurler {
+    agent: ureq::Agent,
+    max_bytes: u64,
+}
+
+impl Unfurler {
+    /// Builds an unfurler giving up on pages after `timeout`, reading at most
+    /// `max_bytes` of each. `allow_private` lets it connect to private networks, which
+    /// is only meant for local testing.
+    pub fn new(timeout: Duration, max_bytes: u64, allow_private: bool) -> Self {
+        // Every connection, including redirects, goes through the resolver, so the
+        // addresses checked are the ones actually connected to
+        let resolver = move |netloc: &str| -> io::Result<Vec<SocketAddr>> {
+            let addresses = netloc
+                .to_socket_addrs()?
+                .filter(|address| allow_private || is_public(address.ip()))
+                .collect::<Vec<_>>();
+            if addresses.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "Address is not public",
+                ));
+            }
+            Ok(addresses)
+        };
+
+        Unfurler {
+            agent: ureq::AgentBuilder::new()
+                .timeout_connect(timeout)
+                .timeout(timeout)
+                .redirects(MAX_REDIRECTS)
+                .resolver(resolver)
+                .user_agent(concat!(env!("CARGO_PKG_NAME"), " link preview"))
+                .build(),
+            max_bytes,
+        }
+    }
+
+    /// Builds an unfurler with its limits read from the environment:
+    /// `LINK_CARDS_TIMEOUT_SECONDS`, `LINK_CARDS_MAX_BYTES` and
+    /// `LINK_CARDS_ALLOW_PRIVATE_NETWORKS`
+    pub fn from_env() -> Self {
+        Self::new(
+            Duration::from_secs(config::env_or("LINK_CARDS_TIMEOUT_SECONDS", 5)),
+            config::env_or("LINK_CARDS_MAX_BYTES", 1024 * 1024),
+            config::env_or("LINK_CARDS_ALLOW_PRIVATE_NETWORKS", false),
+        )
+    }
+
+    /// Fetches a web page and reads its OpenGraph and Twitter Card metadata
+    ///
+    /// Pages that are not HTML or have no title get no card.
+    pub fn fetch(&self, url: &str) -> Result<Option<PageCard>, FetchError> {
+        let parsed = Url::parse(url).map_err(|_| FetchError::Permanent("Invalid URL"))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(FetchError::Permanent("Unsupported URL scheme"));
+        }
+
+        let response = self
+            .agent
+            .request_url("GET", &parsed)
+            .set("Accept", "text/html,application/xhtml+xml")
+            .call()
+            .map_err(|e| classify(&e))?;
+
+        if !matches!(
+            response.content_type(),
+            "text/html" | "application/xhtml+xml"
+        ) {
+            return Ok(None);
+        }
+
+        // Relative image URLs are relative to where redirects ended up
+        let base =
+            Url::parse(response.get_url()).map_err(|_| FetchError::Permanent("Invalid URL"))?;
+
+        // Metadata is at the start of the page, so the rest is not needed
+        let mut body = Vec::new();
+        response
+            .into_reader()
+            .take(self.max_bytes)
+            .read_to_end(&mut body)
+            .map_err(|_| FetchError::Transient("Failed to read page"))?;
+
+        Ok(parse(&String::from_utf8_lossy(&body), &base))
+    }
+}
+
+// Tells the failures worth trying again later from the others
+fn classify(error: &ureq::Error) -> FetchError {
+    match error {
+        ureq::Error::Status(408 | 429 | 500.., _) => {
+            FetchError::Transient("Page is temporarily unavailable")
+        }
+        ureq::Error::Status(..) => FetchError::Permanent("Page is not available"),
+        ureq::Error::Transport(transport) => {
+            // The resolver refuses addresses that are not public
+            let refused = std::error::Error::source(error)
+                .and_then(|source| source.downcast_ref::<io::Error>())
+                .is_some_and(|source| source.kind() == io::ErrorKind::PermissionDenied);
+
+            match transport.kind() {
+                _ if refused => FetchError::Permanent("Address is not public"),
+                ureq::ErrorKind::Dns | ureq::ErrorKind::ConnectionFailed | ureq::ErrorKind::Io => {
+                    FetchError::Transient("Failed to fetch page")
+                }
+                _ => FetchError::Permanent("Failed to fetch page"),
+            }
+        }
+    }
+}
+
+/// Whether an address is on the public internet, as opposed to loopback, private,
+/// link-local, shared, reserved or multicast ranges
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_v4(ip),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        // This network, shared address space and IETF protocol assignments
+        || a == 0
+        || (a == 100 && (64..128).contains(&b))
+        || (a == 192 && b == 0 && c == 0)
+        // Benchmarking and reserved
+        || (a == 198 && (18..20).contains(&b))
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local and link-local
+        || (segments[0] & 0xfe00) == 0xfc00
+        || (segments[0] & 0xffc0) == 0xfe80
+        // Documentation
+        || (segments[0] == 0x2001 && segments[1] == 0x0db8)
+        // NAT64, which could reach private IPv4 addresses
+        || (segments[0] == 0x0064 && segments[1] == 0xff9b))
+}
+
+/// Reads the preview card of a web page from its OpenGraph and Twitter Card
+/// metadata, falling back to its `<title>` and description
+pub fn parse(html: &str, base: &Url) -> Option<PageCard> {
+    let lowercase = html.to_ascii_lowercase();
+    // Only the head of the page is read
+    let head_end = lowercase.find("<body").unwrap_or(html.len());
+    let (html, lowercase) = (&html[..head_end], &lowercase[..head_end]);
+
+    let mut properties: Vec<(String, String)> = Vec::new();
+    let mut search_from = 0;
+    while let Some(offset) = lowercase[search_from..].find("<meta") {
+        let start = search_from + offset + "<meta".len();
+        let (attributes, end) = parse_attributes(&html[start..]);
+        search_from = start + end;
+
+        let key = attributes
+            .iter()
+            .find(|(name, _)| name == "property" || name == "name")
+            .map(|(_, value)| value.to_ascii_lowercase());
+        let content = attributes
+            .iter()
+            .find(|(name, _)| name == "content")
+            .map(|(_, value)| value.clone());
+        if let (Some(key), Some(content)) = (key, content) {
+            properties.push((key, content));
+        }
+    }
+
+    // The first non-blank value of the first key found
+    let find = |keys: &[&str]| {
+        keys.iter().find_map(|key| {
+            properties
+                .iter()
+                .filter(|(name, _)| name == key)
+                .map(|(_, value)| collapse_whitespace(value))
+                .find(|value| !value.is_empty())
+        })
+    };
+
+    let title = find(&["og:title", "twitter:title"]).or_else(|| {
+        let start = lowercase.find("<title")?;
+        let start = start + lowercase[start..].find('>')? + 1;
+        let end = start + lowercase[start..].find("</title")?;
+        Some(collapse_whitespace(&decode_entities(&html[start..end]))).filter(|t| !t.is_empty())
+    })?;
+
+    let image_url = find(&[
+        "og:image:secure_url",
+        "og:image:url",
+        "og:image",
+        "twitter:image",
+        "twitter:image:src",
+    ])
+    .and_then(|image| base.join(&image).ok())
+    .filter(|image| matches!(image.scheme(), "http" | "https"))
+    .map(String::from);
+
+    let card_type = match find(&["twitter:card"]).as_deref() {
+        Some("summary_large_image") => "summary_large_image",
+        _ => "summary",
+    };
+
+    Some(PageCard {
+        card_type: card_type.to_string(),
+        title: truncate(title, MAX_TITLE_LENGTH),
+        description: find(&["og:description", "twitter:description", "description"])
+            .map(|description| truncate(description, MAX_DESCRIPTION_LENGTH)),
+        image_url,
+        site_name: find(&["og:site_name"]).map(|name| truncate(name, MAX_TITLE_LENGTH)),
+    })
+}
+
+// Parses the attributes of a tag, given the text right after its name. Returns the
+// attributes, with lowercase names and decoded values, and where the tag ends.
+fn parse_attributes(text: &str) -> (Vec<(String, String)>, usize) {
+    let mut attributes = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    loop {
+        while chars
+            .next_if(|(_, c)| c.is_whitespace() || *c == '/')
+            .is_some()
+        {}
+
+        let Some((name_start, c)) = chars.next() else {
+            return (attributes, text.len());
+        };
+        if c == '>' {
+            return (attributes, name_start + 1);
+        }
+
+        let mut name_end = text.len();
+        while let Some((i, c)) = chars.peek().copied() {
+            if c.is_whitespace() || c == '=' || c == '>' || c == '/' {
+                name_end = i;
+                break;
+            }
+            chars.next();
+        }
+        let name = text[name_start..name_end].to_ascii_lowercase();
+
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        if chars.next_if(|(_, c)| *c == '=').is_none() {
+            attributes.push((name, String::new()));
+            continue;
+        }
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+
+        let value = match chars.peek().copied() {
+            Some((i, quote @ ('"' | '\''))) => {
+                chars.next();
+                let start = i + 1;
+                let end = chars
+                    .find(|(_, c)| *c == quote)
+                    .map_or(text.len(), |(end, _)| end);
+                &text[start..end]
+            }
+            Some((start, _)) => {
+                let mut end = text.len();
+                while let Some((i, c)) = chars.peek().copied() {
+                    if c.is_whitespace() || c == '>' {
+                        end = i;
+                        break;
+                    }
+                    chars.next();
+                }
+                &text[start..end]
+            }
+            None => "",
+        };
+        attributes.push((name, decode_entities(value)));
+    }
+}
+
+// Decodes the character references found in attribute values and titles
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let Some(end) = rest.find(';').filter(|end| *end <= 10) else {
+            decoded.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..end];
+        let character = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{A0}'),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(|decimal| decimal.parse()))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+
+        match character {
+            Some(character) => {
+                decoded.push(character);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn truncate(text: String, max_length: usize) -> String {
+    match text.char_indices().nth(max_length) {
+        Some((end, _)) => format!("{}…", text[..end].trim_end()),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Instant;
+
+    use crate::util::test_server::{self, Response};
+
+    const PAGE: &str = r#"<!doctype html>
+<html>
+<head>
+  <title>Fallback title</title>
+  <meta property="og:title" content="Rust &amp; friends">
+  <meta property="og:description" content="  A language   empowering everyone ">
+  <meta property="og:image" content="images/logo.png">
+  <meta property="og:site_name" content="Rust">
+  <meta name="twitter:card" content="summary_large_image">
+  <meta name="twitter:title" content="Twitter title">
+</head>
+<body><meta property="og:title" content="Not in the head"></body>
+</html>"#;
+
+    fn html(body: impl Into<Vec<u8>>) -> Response {
+        Response::new(200)
+            .header("Content-Type", "text/html; charset=utf-8")
+            .body(body)
+    }
+
+    fn local_unfurler() -> Unfurler {
+        Unfurler::new(Duration::from_secs(2), 1024 * 1024, true)
+    }
+
+    #[test]
+    fn fetch_reads_opengraph_and_twitter_card_metadata() {
+        let server = test_server::serve(|_| html(PAGE));
+
+        let card = local_unfurler()
+            .fetch(&format!("{}/posts/1", server))
+            .unwrap()
+            .unwrap();
+        assert_eq!(card.card_type, "summary_large_image");
+        assert_eq!(card.title, "Rust & friends");
+        assert_eq!(
+            card.description.as_deref(),
+            Some("A language empowering everyone")
+        );
+        assert_eq!(
+            card.image_url,
+            Some(format!("{}/posts/images/logo.png", server))
+        );
+        assert_eq!(card.site_name.as_deref(), Some("Rust"));
+    }
+
+    #[test]
+    fn parse_falls_back_to_twitter_tags_and_the_title() {
+        let base = Url::parse("https://example.com/a/b").unwrap();
+
+        let card = parse(
+            r#"<head><meta name="twitter:title" content="Twitter title">
+            <meta name='twitter:image' content=https://cdn.example.com/i.png>
+            <meta name="description" content="Plain description"></head>"#,
+            &base,
+        )
+        .unwrap();
+        assert_eq!(card.card_type, "summary");
+        assert_eq!(card.title, "Twitter title");
+        assert_eq!(
+            card.image_url.as_deref(),
+            Some("https://cdn.example.com/i.png")
+        );
+        assert_eq!(card.description.as_deref(), Some("Plain description"));
+
+        let card = parse("<title>\n  Caf&#xE9; &lt;3\n</title>", &base).unwrap();
+        assert_eq!(card.title, "Café <3");
+        assert_eq!(card.image_url, None);
+
+        assert!(
+            parse(
+                "<head><meta property=\"og:title\" content=\" \"></head>",
+                &base
+            )
+            .is_none()
+        );
+        assert!(parse("<body><title>In the body</title></body>", &base).is_none());
+    }
+
+    #[test]
+    fn parse_drops_images_that_are_not_web_urls_and_truncates_text() {
+        let base = Url::parse("https://example.com/").unwrap();
+        let html = format!(
+            r#"<meta property="og:title" content="{}"><meta property="og:image" content="javascript:alert(1)">"#,
+            "a".repeat(MAX_TITLE_LENGTH + 10)
+        );
+
+        let card = parse(&html, &base).unwrap();
+        assert_eq!(card.image_url, None);
+        assert_eq!(card.title.chars().count(), MAX_TITLE_LENGTH + 1);
+        assert!(card.title.ends_with('…'));
+    }
+
+    #[test]
+    fn pages_that_are_not_html_have_no_card() {
+        let server = test_server::serve(|_| {
+            Response::new(200)
+                .header("Content-Type", "application/json")
+                .body(r#"{"title": "<title>Not a page</title>"}"#)
+        });
+
+        assert!(local_unfurler().fetch(&server).unwrap().is_none());
+    }
+
+    #[test]
+    fn pages_are_read_up_to_the_byte_limit() {
+        // The metadata comes after more padding than the limit allows to be read
+        let page = format!(
+            "<head><!-- {} --><meta property=\"og:title\" content=\"Late title\"></head>",
+            "x".repeat(4096)
+        );
+        let server = test_server::serve(move |_| html(page.clone()));
+
+        let limited = Unfurler::new(Duration::from_secs(2), 1024, true);
+        assert!(limited.fetch(&server).unwrap().is_none());
+
+        let card = local_unfurler().fetch(&server).unwrap().unwrap();
+        assert_eq!(card.title, "Late title");
+    }
+
+    #[test]
+    fn slow_pages_time_out() {
+        let server = test_server::serve(|_| html(PAGE).delay(Duration::from_secs(3)));
+        let unfurler = Unfurler::new(Duration::from_millis(300), 1024 * 1024, true);
+
+        let started = Instant::now();
+        assert_eq!(
+            unfurler.fetch(&server).unwrap_err(),
+            FetchError::Transient("Failed to fetch page")
+        );
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn server_errors_are_transient_and_missing_pages_permanent() {
+        let server = test_server::serve(|req| match req.path.as_str() {
+            "/busy" => Response::new(503),
+            "/slow-down" => Response::new(429),
+            "/gone" => Response::new(410),
+            _ => Response::new(404),
+        });
+        // Nothing listens on the port of a server that was dropped
+        let closed = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+
+        let cases = [
+            (format!("{}/busy", server), true),
+            (format!("{}/slow-down", server), true),
+            (closed, true),
+            (format!("{}/gone", server), false),
+            (format!("{}/missing", server), false),
+        ];
+        for (url, transient) in cases {
+            let error = local_unfurler().fetch(&url).unwrap_err();
+            assert_eq!(
+                matches!(error, FetchError::Transient(_)),
+                transient,
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn redirects_are_followed_up_to_a_limit() {
+        let server = test_server::serve(|req| match req.path.as_str() {
+            "/short" => Response::new(301).header("Location", "/articles/rust"),
+            "/articles/rust" => html(PAGE),
+            path => {
+                // Loops through /loop/1, /loop/2, and so on
+                let hop: u32 = path.trim_start_matches("/loop/").parse().unwrap_or(0);
+                Response::new(302).header("Location", &format!("/loop/{}", hop + 1))
+            }
+        });
+
+        let card = local_unfurler()
+            .fetch(&format!("{}/short", server))
+            .unwrap()
+            .unwrap();
+        assert_eq!(card.title, "Rust & friends");
+        // Relative URLs are resolved against the page redirected to
+        assert_eq!(
+            card.image_url,
+            Some(format!("{}/articles/images/logo.png", server))
+        );
+
+        assert!(matches!(
+            local_unfurler().fetch(&format!("{}/loop/0", server)),
+            Err(FetchError::Permanent(_))
+        ));
+    }
+
+    #[test]
+    fn private_networks_are_refused_unless_allowed() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let server = {
+            let requests = Arc::clone(&requests);
+            test_server::serve(move |_| {
+                requests.fetch_add(1, Ordering::SeqCst);
+                html(PAGE)
+            })
+        };
+
+        let unfurler = Unfurler::new(Duration::from_secs(2), 1024 * 1024, false);
+        assert_eq!(
+            unfurler.fetch(&server).unwrap_err(),
+            FetchError::Permanent("Address is not public")
+        );
+        let localhost = server.replace("127.0.0.1", "localhost");
+        assert_eq!(
+            unfurler.fetch(&localhost).unwrap_err(),
+            FetchError::Permanent("Address is not public")
+        );
+        assert_eq!(requests.load(Ordering::SeqCst), 0);
+
+        assert!(local_unfurler().fetch(&server).unwrap().is_some());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn only_web_urls_are_fetched() {
+        let unfurler = local_unfurler();
+        assert_eq!(
+            unfurler.fetch("ftp://example.com/").unwrap_err(),
+            FetchError::Permanent("Unsupported URL scheme")
+        );
+        assert_eq!(
+            unfurler.fetch("file:///etc/passwd").unwrap_err(),
+            FetchError::Permanent("Unsupported URL scheme")
+        );
+        assert_eq!(
+            unfurler.fetch("not a url").unwrap_err(),
+            FetchError::Permanent("Invalid URL")
+        );
+    }
+
+    #[test]
+    fn is_public_refuses_private_and_reserved_ipv4() {
+        let cases = [
+            ("8.8.8.8", true),
+            ("1.1.1.1", true),
+            ("100.63.255.255", true),
+            ("100.128.0.0", true),
+            ("172.32.0.1", true),
+            ("0.0.0.0", false),
+            ("0.1.2.3", false),
+            ("127.0.0.1", false),
+            ("127.255.255.254", false),
+            ("10.0.0.1", false),
+            ("172.16.0.1", false),
+            ("172.31.255.255", false),
+            ("192.168.1.1", false),
+            // Shared address space (carrier-grade NAT)
+            ("100.64.0.1", false),
+            ("100.127.255.255", false),
+            ("169.254.169.254", false),
+            ("192.0.0.8", false),
+            ("192.0.2.1", false),
+            ("198.18.0.1", false),
+            ("198.19.255.255", false),
+            ("198.51.100.1", false),
+            ("203.0.113.1", false),
+            ("224.0.0.1", false),
+            ("240.0.0.1", false),
+            ("255.255.255.255", false),
+        ];
+
+        for (ip, public) in cases {
+            assert_eq!(is_public(ip.parse().unwrap()), public, "{}", ip);
+        }
+    }
+
+    #[test]
+    fn is_public_refuses_private_and_reserved_ipv6() {
+        let cases = [
+            ("2606:4700:4700::1111", true),
+            ("2001:4860:4860::8888", true),
+            ("::", false),
+            ("::1", false),
+            // Unique local
+            ("fc00::1", false),
+            ("fd12:3456:789a::1", false),
+            // Link-local
+            ("fe80::1", false),
+            ("febf::1", false),
+            ("ff02::1", false),
+            ("2001:db8::1", false),
+            // IPv4-mapped addresses are checked as IPv4
+            ("::ffff:8.8.8.8", true),
+            ("::ffff:127.0.0.1", false),
+            ("::ffff:10.0.0.1", false),
+            ("::ffff:169.254.169.254", false),
+            // NAT64
+            ("64:ff9b::8.8.8.8", false),
+            ("64:ff9b::a00:1", false),
+        ];
+
+        for (ip, public) in cases {
+            assert_eq!(is_public(ip.parse().unwrap()), public, "{}", ip);
+        }
+    }
+}
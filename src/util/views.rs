@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use crate::models::post_view::PostView;
+
+// Views kept in memory at most, should writing them fall behind
+const MAX_PENDING_VIEWS: usize = 100_000;
+
+/// How a post was seen
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ViewKind {
+    /// The post was shown in a list, such as a timeline
+    Impression,
+    /// The post itself was opened
+    Detail,
+}
+
+impl ViewKind {
+    /// Name of the kind of view in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ViewKind::Impression => "impression",
+            ViewKind::Detail => "detail",
+        }
+    }
+}
+
+/// A view of a post that has not been counted yet
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct PendingView {
+    pub post_id: i32,
+    pub viewer_id: i32,
+    pub kind: ViewKind,
+}
+
+/// Collects views of posts in memory, for the view counts job to write them in
+/// batches and keep reads fast
+///
+/// Authors viewing their own posts are not counted.
+#[derive(Default)]
+pub struct ViewRecorder {
+    pending: Mutex<HashSet<PendingView>>,
+}
+
+impl ViewRecorder {
+    /// Records that `viewer_id` opened a post
+    pub fn record_detail_view(&self, viewer_id: i32, post: &PostView) {
+        self.record(Self::views(viewer_id, ViewKind::Detail, [post]));
+    }
+
+    /// Records that `viewer_id` was shown a list of posts, not counting the posts they
+    /// quote
+    pub fn record_impressions(&self, viewer_id: i32, posts: &[PostView]) {
+        self.record(Self::views(viewer_id, ViewKind::Impression, posts));
+    }
+
+    /// Records views, such as those the view counts job could not write
+    pub fn record(&self, views: impl IntoIterator<Item = PendingView>) {
+        let mut pending = self.pending.lock().unwrap();
+        for view in views {
+            if pending.len() >= MAX_PENDING_VIEWS {
+                break;
+            }
+            pending.insert(view);
+        }
+    }
+
+    /// Takes the views recorded since the last call
+    pub fn take(&self) -> Vec<PendingView> {
+        self.pending.lock().unwrap().drain().collect()
+    }
+
+    fn views<'a>(
+        viewer_id: i32,
+        kind: ViewKind,
+        posts: impl IntoIterator<Item = &'a PostView>,
+    ) -> impl Iterator<Item = PendingView> {
+        posts
+            .into_iter()
+            .filter(move |post| post.user_id != viewer_id)
+            .map(move |post| PendingView {
+                post_id: post.id,
+                viewer_id,
+                kind,
+            })
+    }
+}